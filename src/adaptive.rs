@@ -0,0 +1,137 @@
+use crate::data::OptimizationData;
+use crate::utils::generator;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::SQRT_2;
+
+pub const MUTATION_OPERATORS: [&str; 2] = ["bitflip", "prob_bitflip"];
+pub const RECOMBINATION_OPERATORS: [&str; 2] = ["one_point", "two_point"];
+
+const UCB_EXPLORATION: f64 = SQRT_2;
+const MATCHING_ADAPTATION_RATE: f64 = 0.3;
+const MATCHING_MIN_PROBABILITY: f64 = 0.1;
+const USAGE_WINDOWS: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperatorBandit {
+    pub operators: Vec<String>,
+    pub strategy: String,
+    pub counts: Vec<usize>,
+    pub qualities: Vec<f64>,
+    pub history: Vec<usize>,
+}
+
+pub fn build_bandit(operators: &[&str], optimization_data: &OptimizationData) -> OperatorBandit {
+    OperatorBandit {
+        operators: operators
+            .iter()
+            .map(|operator| operator.to_string())
+            .collect(),
+        strategy: optimization_data.adaptive_strategy.clone(),
+        counts: vec![0; operators.len()],
+        qualities: vec![0.0; operators.len()],
+        history: Vec::new(),
+    }
+}
+
+fn select_ucb(bandit: &OperatorBandit) -> usize {
+    if let Some(untried) = bandit.counts.iter().position(|count| *count == 0) {
+        return untried;
+    }
+
+    let total = bandit.history.len() as f64;
+    let mut selected = 0;
+    let mut selected_score = f64::MIN;
+    for index in 0..bandit.operators.len() {
+        let score = bandit.qualities[index]
+            + UCB_EXPLORATION * (total.ln() / bandit.counts[index] as f64).sqrt();
+        if score > selected_score {
+            selected = index;
+            selected_score = score;
+        }
+    }
+    selected
+}
+
+fn select_probability_matching(bandit: &OperatorBandit) -> usize {
+    let mut rng = generator();
+    let operator_count = bandit.operators.len() as f64;
+    let quality_sum: f64 = bandit.qualities.iter().sum();
+
+    let mut threshold = rng.gen::<f64>();
+    for index in 0..bandit.operators.len() {
+        let probability = if quality_sum > 0.0 {
+            MATCHING_MIN_PROBABILITY
+                + (1.0 - operator_count * MATCHING_MIN_PROBABILITY) * bandit.qualities[index]
+                    / quality_sum
+        } else {
+            1.0 / operator_count
+        };
+        if threshold < probability {
+            return index;
+        }
+        threshold -= probability;
+    }
+    bandit.operators.len() - 1
+}
+
+/// Chooses the operator to use for the next iteration and records the choice.
+pub fn select_operator(bandit: &mut OperatorBandit) -> String {
+    let selected = if bandit.strategy == "probability_matching" {
+        select_probability_matching(bandit)
+    } else {
+        select_ucb(bandit)
+    };
+    bandit.history.push(selected);
+    bandit.operators[selected].clone()
+}
+
+/// Rewards the most recently selected operator, reward should lie between 0 and 1.
+pub fn update_operator(bandit: &mut OperatorBandit, reward: f64) {
+    let selected = match bandit.history.last() {
+        Some(selected) => *selected,
+        None => return,
+    };
+    bandit.counts[selected] += 1;
+    if bandit.strategy == "probability_matching" {
+        bandit.qualities[selected] +=
+            MATCHING_ADAPTATION_RATE * (reward - bandit.qualities[selected]);
+    } else {
+        bandit.qualities[selected] +=
+            (reward - bandit.qualities[selected]) / bandit.counts[selected] as f64;
+    }
+}
+
+pub fn print_operator_usage(bandit: &OperatorBandit, name: &str) {
+    if bandit.history.is_empty() {
+        return;
+    }
+    let window_size = ((bandit.history.len() as f64) / USAGE_WINDOWS as f64).ceil() as usize;
+
+    println!("{} operator usage:", name);
+    print!("iterations");
+    for operator in bandit.operators.iter() {
+        print!("\t{}", operator);
+    }
+    println!();
+    for (window, selections) in bandit.history.chunks(window_size).enumerate() {
+        print!(
+            "{}-{}",
+            window * window_size + 1,
+            window * window_size + selections.len()
+        );
+        for index in 0..bandit.operators.len() {
+            let used = selections
+                .iter()
+                .filter(|selected| **selected == index)
+                .count();
+            print!("\t{:.2}", used as f64 / selections.len() as f64);
+        }
+        println!();
+    }
+    print!("total");
+    for index in 0..bandit.operators.len() {
+        print!("\t{}", bandit.counts[index]);
+    }
+    println!();
+}
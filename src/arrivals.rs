@@ -0,0 +1,91 @@
+use crate::data::SimulationData;
+use crate::data::TrafficState;
+use crate::utils::generator;
+use rand::Rng;
+use rand_distr::{Distribution, Gamma, Poisson};
+
+pub const ARRIVALS: [&str; 3] = ["deterministic", "poisson", "negative_binomial"];
+pub const ROBUST_FITNESS: [&str; 3] = ["mean", "worst", "cvar"];
+
+/// Random number of cars with the given mean. Negative binomial counts are Poisson counts with
+/// a gamma distributed mean, their variance is `mean + mean^2 / dispersion`.
+fn sample_cars<R: Rng>(mean: i32, arrivals: &str, dispersion: f64, rng: &mut R) -> i32 {
+    if mean <= 0 || arrivals == "deterministic" {
+        return mean;
+    }
+    let mut rate = mean as f64;
+    if arrivals == "negative_binomial" {
+        rate = Gamma::new(dispersion, rate / dispersion)
+            .unwrap()
+            .sample(rng);
+        if rate <= 0.0 {
+            return 0;
+        }
+    }
+    let cars: f64 = Poisson::new(rate).unwrap().sample(rng);
+    cars as i32
+}
+
+/// Samples the cars of every approach, intersection and timestep around the given traffic data.
+pub fn sample_traffic_data(
+    traffic_data: &Vec<Vec<TrafficState>>,
+    arrivals: &str,
+    dispersion: f64,
+) -> Vec<Vec<TrafficState>> {
+    let mut rng = generator();
+    traffic_data
+        .iter()
+        .map(|intersection| {
+            intersection
+                .iter()
+                .map(|traffic| TrafficState {
+                    main_from_prev: sample_cars(
+                        traffic.main_from_prev,
+                        arrivals,
+                        dispersion,
+                        &mut rng,
+                    ),
+                    main_from_next: sample_cars(
+                        traffic.main_from_next,
+                        arrivals,
+                        dispersion,
+                        &mut rng,
+                    ),
+                    side: sample_cars(traffic.side, arrivals, dispersion, &mut rng),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Samples the traffic data realizations all candidates are simulated on, so candidates are
+/// compared on the same arrivals. Deterministic arrivals need no samples.
+pub fn sample_realizations(simulation_data: &SimulationData) -> Vec<Vec<Vec<TrafficState>>> {
+    if simulation_data.arrivals == "deterministic" {
+        return Vec::new();
+    }
+    (0..simulation_data.sample_count)
+        .map(|_| {
+            sample_traffic_data(
+                &simulation_data.traffic_data,
+                &simulation_data.arrivals,
+                simulation_data.dispersion,
+            )
+        })
+        .collect()
+}
+
+/// Combines the fitness values of all realizations into the mean, the worst value or the
+/// conditional value at risk, the mean of the worst `cvar_alpha` share of the values.
+pub fn get_robust_value(values: &Vec<f64>, robust_fitness: &str, cvar_alpha: f64) -> f64 {
+    let mut sorted = values.clone();
+    sorted.sort_by(|value1, value2| value1.total_cmp(value2));
+    let count = if robust_fitness == "worst" {
+        1
+    } else if robust_fitness == "cvar" {
+        ((cvar_alpha * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len())
+    } else {
+        sorted.len()
+    };
+    sorted[..count].iter().sum::<f64>() / count as f64
+}
@@ -0,0 +1,136 @@
+use crate::data::format_candidate;
+use crate::data::ConfigurationData;
+use crate::data::GenerationData;
+use crate::data::OptimizationData;
+use crate::data::OptimizationResult;
+use crate::data::PlotData;
+use crate::data::SimulationData;
+use crate::optimization::optimize;
+use crate::output::build_run_record;
+use crate::output::RunRecord;
+use crate::plot::draw_benchmark_plot;
+use crate::statistics::get_statistics;
+use std::fs;
+
+fn print_statistics(name: &str, values: &Vec<f64>) {
+    let statistics = get_statistics(values);
+    let standard_deviation = match statistics.standard_deviation {
+        Some(standard_deviation) => format!("{:.4}", standard_deviation),
+        None => String::from("n/a"),
+    };
+    let confidence_interval = match statistics.confidence_interval {
+        Some((low, high)) => format!("[{:.4}, {:.4}]", low, high),
+        None => String::from("n/a"),
+    };
+    println!(
+        "{}\t{:.4}\t{}\t{:.4}\t{:.4}\t{:.4}\t{:.4}\t{:.4}\t{}",
+        name,
+        statistics.mean,
+        standard_deviation,
+        statistics.min,
+        statistics.first_quartile,
+        statistics.median,
+        statistics.third_quartile,
+        statistics.max,
+        confidence_interval
+    );
+}
+
+/// Writes one line per run with its results and best candidate.
+fn write_runs_file(path: &str, results: &Vec<OptimizationResult>) -> Result<(), String> {
+    let mut content =
+        String::from("run,best_value,duration,iterations,evaluations,best_candidate\n");
+    for (run, result) in results.iter().enumerate() {
+        content.push_str(&format!(
+            "{},{},{},{},{},\"{}\"\n",
+            run,
+            result.best_value,
+            result.duration,
+            result.iterations,
+            result.evaluations,
+            format_candidate(&result.best_candidate)
+        ));
+    }
+    fs::write(path, content)
+        .map_err(|error| format!("Could not write run results to {}: {}", path, error))
+}
+
+/// Runs the optimization the configured amount of times and prints statistics of the results.
+/// With plotting enabled the best values of all runs are drawn as median with bands.
+/// Returns the run records of all runs.
+pub fn benchmark(
+    configuration_data: &ConfigurationData,
+    optimization_data: &OptimizationData,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    plot_data: &mut PlotData,
+) -> Result<Vec<RunRecord>, String> {
+    if configuration_data.benchmark_iterations < 1 {
+        return Err(String::from("Benchmark needs at least one run"));
+    }
+    let mut runs: Vec<RunRecord> = Vec::new();
+    let mut results: Vec<OptimizationResult> = Vec::new();
+    let mut run_best_values: Vec<Vec<f64>> = Vec::new();
+    for _ in 0..configuration_data.benchmark_iterations {
+        *plot_data = PlotData::default();
+        let result = optimize(
+            configuration_data,
+            optimization_data,
+            simulation_data,
+            generation_data,
+            plot_data,
+        );
+        runs.push(build_run_record(&result, plot_data));
+        run_best_values.push(plot_data.best_values.clone());
+        results.push(result);
+    }
+
+    if configuration_data.plot {
+        draw_benchmark_plot(configuration_data, &run_best_values, optimization_data)?;
+    }
+
+    if let Some(path) = &configuration_data.runs_file {
+        write_runs_file(path, &results)?;
+    }
+
+    // The summary would mix with the run record on stdout
+    if configuration_data.output.is_some() && configuration_data.output_file.is_none() {
+        return Ok(runs);
+    }
+    println!("Statistics over {} runs:", results.len());
+    println!("\tmean\tstd\tmin\tq1\tmedian\tq3\tmax\t95% ci of mean");
+    print_statistics(
+        "best_value",
+        &results.iter().map(|result| result.best_value).collect(),
+    );
+    print_statistics(
+        "duration",
+        &results.iter().map(|result| result.duration).collect(),
+    );
+    print_statistics(
+        "iterations",
+        &results
+            .iter()
+            .map(|result| result.iterations as f64)
+            .collect(),
+    );
+    print_statistics(
+        "evaluations",
+        &results
+            .iter()
+            .map(|result| result.evaluations as f64)
+            .collect(),
+    );
+
+    let (best_run, best_result) = results
+        .iter()
+        .enumerate()
+        .max_by(|(_, result1), (_, result2)| result1.best_value.total_cmp(&result2.best_value))
+        .unwrap();
+    println!("Best candidate over all runs (run {}):", best_run);
+    println!(
+        "{:?}\t{:.4}",
+        best_result.best_candidate, best_result.best_value
+    );
+    Ok(runs)
+}
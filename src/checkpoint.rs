@@ -0,0 +1,117 @@
+use crate::data::ConfigurationData;
+use crate::data::GenerationData;
+use crate::data::OptimizationData;
+use crate::data::PlotData;
+use crate::data::SimulationData;
+use crate::optimization::OptimizerState;
+use crate::utils::get_generator_state;
+use crate::utils::GeneratorState;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub configuration_data: ConfigurationData,
+    pub generation_data: GenerationData,
+    pub optimization_data: OptimizationData,
+    pub simulation_data: SimulationData,
+    pub state: OptimizerState,
+    pub generator_state: GeneratorState,
+    pub plot_data: PlotData,
+}
+
+/// Serializes a candidate as its bit strings joined by commas.
+pub mod candidate_format {
+    use crate::data::{format_candidate, parse_bits};
+    use bit_vec::BitVec;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        candidate: &Vec<BitVec>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format_candidate(candidate))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<BitVec>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse_bits(&text).map_err(D::Error::custom)
+    }
+}
+
+/// Serializes a list of candidates as a list of their bit strings joined by commas.
+pub mod candidates_format {
+    use crate::data::{format_candidate, parse_bits};
+    use bit_vec::BitVec;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        candidates: &Vec<Vec<BitVec>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(candidates.iter().map(format_candidate))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<BitVec>>, D::Error> {
+        let texts = Vec::<String>::deserialize(deserializer)?;
+        texts
+            .iter()
+            .map(|text| parse_bits(text).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+/// Writes the checkpoint to a temporary file first and renames it afterwards, so an
+/// interruption while writing never destroys the previous checkpoint.
+pub fn write_checkpoint(path: &str, checkpoint: &Checkpoint) -> Result<(), String> {
+    let content = serde_json::to_string(checkpoint)
+        .map_err(|error| format!("Could not serialize checkpoint: {}", error))?;
+    let temporary_path = format!("{}.tmp", path);
+    fs::write(&temporary_path, content).map_err(|error| {
+        format!(
+            "Could not write checkpoint to {}: {}",
+            temporary_path, error
+        )
+    })?;
+    fs::rename(&temporary_path, path)
+        .map_err(|error| format!("Could not write checkpoint to {}: {}", path, error))
+}
+
+pub fn load_checkpoint(path: &str) -> Result<Checkpoint, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|error| format!("Could not read checkpoint from {}: {}", path, error))?;
+    serde_json::from_str(&content)
+        .map_err(|error| format!("Could not parse checkpoint {}: {}", path, error))
+}
+
+pub fn save_checkpoint(
+    configuration_data: &ConfigurationData,
+    optimization_data: &OptimizationData,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    state: &OptimizerState,
+    plot_data: &PlotData,
+) {
+    let path = match &configuration_data.checkpoint {
+        Some(path) => path,
+        None => return,
+    };
+    let checkpoint = Checkpoint {
+        configuration_data: configuration_data.clone(),
+        generation_data: generation_data.clone(),
+        optimization_data: optimization_data.clone(),
+        simulation_data: simulation_data.clone(),
+        state: state.clone(),
+        generator_state: get_generator_state(),
+        plot_data: plot_data.clone(),
+    };
+    if let Err(error) = write_checkpoint(path, &checkpoint) {
+        eprintln!("{}", error);
+    }
+}
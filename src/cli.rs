@@ -0,0 +1,874 @@
+use crate::arrivals::{sample_realizations, ARRIVALS, ROBUST_FITNESS};
+use crate::data::{
+    calculate_max_passthrough, calculate_min_count, fixed_data, generate_data, ConfigurationData,
+    GenerationData, OptimizationData, SimulationData, TrafficState, TuningData,
+};
+use crate::network::{
+    build_grid_links, build_intersection_settings, check_intersection_settings,
+    get_link_turn_shares, get_turn_shares, load_network, DEFAULT_MAIN_PERCENTAGE, DEFAULT_ROWS,
+    DEFAULT_SIDE_PERCENTAGE,
+};
+use crate::parameters::{
+    apply_intersection_settings, check_optimization_data, ADAPTIVE_STRATEGIES, DATA_VARIANTS,
+    MUTATIONS, OPTIMIZATIONS, RECOMBINATIONS, REPLACEMENTS,
+};
+use crate::phases::{get_phase_bits, parse_phases, DEFAULT_PHASES};
+use crate::render::SIMULATION_VIEWS;
+use crate::seeding::generate_seeds;
+use crate::simulation::FITNESS_VALUES;
+use crate::utils::seed_generator;
+use clap::{Args, Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+
+/// Evolutionary algorithm to optimize traffic lights on a linear road with intersections
+#[derive(Parser, Debug)]
+#[clap(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+
+    #[clap(flatten)]
+    pub optimize: OptimizeArgs,
+
+    /// TOML file to load the configuration from, options given on the command line override it
+    #[clap(long, global = true)]
+    pub config: Option<String>,
+
+    /// File to write the effective configuration of the run to as TOML
+    #[clap(long, global = true)]
+    pub dump_config: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate car traffic data and print it
+    Generate(GenerateArgs),
+
+    /// Optimize the traffic lights, the default without a subcommand
+    Optimize(OptimizeArgs),
+
+    /// Evaluate given signal plans without optimizing
+    Evaluate(EvaluateArgs),
+
+    /// Run the optimization a set amount of times and show statistics of the results
+    Benchmark(BenchmarkArgs),
+
+    /// Run several optimizer configurations on the same scenario and compare their results
+    Compare(CompareArgs),
+
+    /// Search the parameter space given by the tune options for the best configuration
+    Tune(TuneArgs),
+
+    /// Run all combinations of a parameter grid on several scenarios and write a results table
+    Sweep(SweepArgs),
+}
+
+#[derive(Args, Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationArgs {
+    /// Number of intersections for the traffic simulation
+    #[clap(long, default_value_t = 8)]
+    pub intersections: usize,
+
+    /// Number of timesteps for the traffic simulation
+    #[clap(long, default_value_t = 16)]
+    pub timesteps: usize,
+
+    /// Maximum number of cars possible on the main road
+    #[clap(long, default_value_t = 20)]
+    pub main_max_count: i32,
+
+    /// Maximum number of cars possible on the side roads
+    #[clap(long, default_value_t = 10)]
+    pub side_max_count: i32,
+
+    /// Seed for the random number generator to make runs reproducible
+    #[clap(long)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Args, Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationArgs {
+    /// Car traffic data to use for the traffic simulation
+    #[clap(short, long, default_value = "fixed", possible_values = DATA_VARIANTS)]
+    pub data: String,
+
+    /// Disable the increasing passthrough to keep max passthrough always the same
+    #[clap(long)]
+    pub disable_increasing_passthrough: bool,
+
+    /// Disable the max passthrough value to not limit cars per timestep
+    #[clap(long)]
+    pub disable_max_passthrough: bool,
+
+    /// Amount of cars staying on the main road
+    #[clap(long, default_value_t = DEFAULT_MAIN_PERCENTAGE)]
+    pub main_percentage: f64,
+
+    /// Amount of cars coming to main road from side roads
+    #[clap(long, default_value_t = DEFAULT_SIDE_PERCENTAGE)]
+    pub side_percentage: f64,
+
+    /// Number of rows of parallel streets the intersections are laid out in, connected by one-way
+    /// cross streets, 1 is a single street
+    #[clap(long, default_value_t = DEFAULT_ROWS)]
+    pub rows: usize,
+
+    /// TOML file defining the intersections and the links of the road network, replacing the
+    /// street or grid
+    #[clap(long)]
+    pub network: Option<String>,
+
+    /// Phases, max passthrough and turning percentages of one intersection given as
+    /// index:name=value,..., e.g. 2:side_max_passthrough=8,phases=main/main_from_next_turn/side,
+    /// overriding the global values
+    #[clap(long = "intersection", multiple_occurrences = true)]
+    pub intersection_settings: Vec<String>,
+
+    /// Phases the intersections switch between unless given for an intersection, separated by
+    /// commas or slashes, each serving the movements joined by + (main, an approach like side or
+    /// a turn like main_from_next_turn)
+    #[clap(long, default_value = DEFAULT_PHASES)]
+    pub phases: String,
+
+    /// Length of a timestep in seconds
+    #[clap(long, default_value_t = 10.0)]
+    pub timestep_length: f64,
+
+    /// Yellow time in seconds ending the previous phase when a light switches
+    #[clap(long, default_value_t = 0.0)]
+    pub yellow_time: f64,
+
+    /// All-red time in seconds after the yellow time when a light switches
+    #[clap(long, default_value_t = 0.0)]
+    pub all_red_time: f64,
+
+    /// Start-up lost time in seconds of the queues getting green when a light switches
+    #[clap(long, default_value_t = 0.0)]
+    pub startup_lost_time: f64,
+
+    /// Number of cars fitting into every link between two intersections without a capacity of
+    /// its own, including the cross streets of a grid, no cars pass an intersection into a full
+    /// link
+    #[clap(long)]
+    pub link_capacity: Option<i32>,
+
+    /// Distribution to sample the arriving cars from around the traffic data
+    #[clap(long, default_value = "deterministic", possible_values = ARRIVALS)]
+    pub arrivals: String,
+
+    /// Number of sampled arrival realizations every candidate is simulated on
+    #[clap(long, default_value_t = 10)]
+    pub samples: usize,
+
+    /// Dispersion of negative binomial arrivals, smaller values give more variance
+    #[clap(long, default_value_t = 2.0)]
+    pub dispersion: f64,
+
+    /// How to combine the fitness values of the sampled realizations
+    #[clap(long, default_value = "mean", possible_values = ROBUST_FITNESS)]
+    pub robust_fitness: String,
+
+    /// Share of the worst realizations averaged by the cvar robust fitness
+    #[clap(long, default_value_t = 0.2)]
+    pub cvar_alpha: f64,
+}
+
+#[derive(Args, Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizerArgs {
+    /// Maximum number of iterations to run
+    #[clap(short, long, default_value_t = 1000)]
+    pub iterations: usize,
+
+    /// Stop after this many iterations without improvement of the best value
+    #[clap(long)]
+    pub stagnation_iterations: Option<usize>,
+
+    /// Stop as soon as the best value reaches this fitness value
+    #[clap(long)]
+    pub target_value: Option<f64>,
+
+    /// Stop after this many seconds
+    #[clap(long)]
+    pub time_limit: Option<f64>,
+
+    /// Stop before the number of simulated candidates would exceed this value
+    #[clap(long)]
+    pub max_evaluations: Option<usize>,
+
+    /// Optimization variant to use
+    #[clap(short, long, default_value = "genetic", possible_values = OPTIMIZATIONS)]
+    pub optimization: String,
+
+    /// Mutation variant to use
+    #[clap(short, long, default_value = "prob_bitflip", possible_values = MUTATIONS)]
+    pub mutation: String,
+
+    /// Mutation variant to use
+    #[clap(short, long, default_value = "two_point", possible_values = RECOMBINATIONS)]
+    pub recombination: String,
+
+    /// Multi-armed bandit strategy to choose operators with in adaptive mutation or recombination
+    #[clap(long, default_value = "ucb", possible_values = ADAPTIVE_STRATEGIES)]
+    pub adaptive_strategy: String,
+
+    /// Probability for bitflip in prob_bitflip mutation
+    #[clap(long, default_value_t = 0.0078125)]
+    pub probability_bitflip: f64,
+
+    /// Probability for bitflip in prob_bitflip mutation
+    #[clap(long, default_value_t = 0.75)]
+    pub probability_recombination: f64,
+
+    /// Population size
+    #[clap(long, default_value_t = 50)]
+    pub population_size: usize,
+
+    /// Parent population size
+    #[clap(long, default_value_t = 10)]
+    pub parents_size: usize,
+
+    /// Tournament size
+    #[clap(long, default_value_t = 5)]
+    pub tournament_size: usize,
+
+    /// Replacement strategy of the genetic algorithm
+    #[clap(long, default_value = "generational", possible_values = REPLACEMENTS)]
+    pub replacement: String,
+
+    /// Use fitness sharing during selection to keep the population diverse
+    #[clap(long)]
+    pub fitness_sharing: bool,
+
+    /// Hamming distance below which candidates share their fitness
+    #[clap(long, default_value_t = 16.0)]
+    pub sharing_radius: f64,
+
+    /// Structured plans to seed the initial population with
+    #[clap(long, use_value_delimiter = true, possible_values = ["all_main", "cycles", "green_wave", "file"])]
+    pub seeding: Vec<String>,
+
+    /// Maximum share of the initial population replaced by seeded plans
+    #[clap(long, default_value_t = 0.2)]
+    pub seed_fraction: f64,
+
+    /// Cycle lengths in timesteps for fixed cycle and green wave seeding
+    #[clap(long, use_value_delimiter = true, default_value = "2,4,8")]
+    pub seed_cycle_lengths: Vec<usize>,
+
+    /// File with plans to seed the initial population with, separated by empty lines
+    #[clap(long)]
+    pub seed_file: Option<String>,
+
+    /// Fitness value to use during optimization
+    #[clap(long, default_value = "ratio", possible_values = FITNESS_VALUES)]
+    pub fitness_value: String,
+}
+
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct ReportArgs {
+    /// Hide output on iterations with improvements
+    #[clap(short, long)]
+    pub silent: bool,
+
+    /// Hide all output of single optimization runs, implies silent
+    #[clap(short, long)]
+    pub quiet: bool,
+
+    /// Print the simulation data for the final best candidate
+    #[clap(long)]
+    pub print_final_simulation: bool,
+
+    /// How to print simulations, a table of the queues of every step, the steps animated in
+    /// place or the raw traffic states
+    #[clap(long, default_value = "steps", possible_values = SIMULATION_VIEWS)]
+    pub simulation_view: String,
+
+    /// Draw plot of best, mean and worst values of each iteration, in benchmarks over all runs
+    #[clap(short, long)]
+    pub plot: bool,
+
+    /// Draw a time-space diagram of the signal plan of the best candidate
+    #[clap(long)]
+    pub time_space_diagram: bool,
+
+    /// Show the platoons of cars passing along the main road in the time-space diagram
+    #[clap(long, requires = "time-space-diagram")]
+    pub platoons: bool,
+
+    /// Draw heatmaps of the queue lengths of every approach per intersection and timestep for the
+    /// best candidate
+    #[clap(long)]
+    pub queue_heatmap: bool,
+
+    /// Plan to draw the queue heatmaps of next to the best candidate, given like the plan to
+    /// evaluate, of a file only the first plan is used
+    #[clap(long, requires = "queue-heatmap")]
+    pub baseline: Option<String>,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub plot_options: PlotArgs,
+
+    /// Write the full run record in a machine-readable format, implies quiet without output file
+    #[clap(long, possible_values = ["json", "csv"])]
+    pub output: Option<String>,
+
+    /// File to write the run record to instead of stdout
+    #[clap(long)]
+    pub output_file: Option<String>,
+}
+
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct PlotArgs {
+    /// Directory to write plots to, created if missing
+    #[clap(long, default_value = "plots")]
+    pub plot_dir: String,
+
+    /// File name of the plots without extension instead of a generated one, further plots of a
+    /// run get their kind appended
+    #[clap(long)]
+    pub plot_file: Option<String>,
+
+    /// Size of the plots in pixels given as WIDTHxHEIGHT
+    #[clap(long, default_value = "800x600")]
+    pub plot_size: String,
+
+    /// Image format of the plots
+    #[clap(long, default_value = "png", possible_values = ["png", "svg"])]
+    pub plot_format: String,
+}
+
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct GenerateArgs {
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub generation: GenerationArgs,
+}
+
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct OptimizeArgs {
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub report: ReportArgs,
+
+    /// File to periodically write the optimizer state to
+    #[clap(long)]
+    pub checkpoint: Option<String>,
+
+    /// Number of iterations between two checkpoints
+    #[clap(long, default_value_t = 100)]
+    pub checkpoint_interval: usize,
+
+    /// Continue an optimization from a checkpoint file with the configuration stored in it
+    #[clap(long)]
+    pub resume: Option<String>,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub generation: GenerationArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub simulation: SimulationArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub optimizer: OptimizerArgs,
+}
+
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct EvaluateArgs {
+    /// Plan given as comma separated bit strings per intersection or a file of plans separated by
+    /// empty lines
+    pub plan: String,
+
+    /// Print the full simulation trace of every plan
+    #[clap(long)]
+    pub print_simulation: bool,
+
+    /// How to print simulations, a table of the queues of every step, the steps animated in
+    /// place or the raw traffic states
+    #[clap(long, default_value = "steps", possible_values = SIMULATION_VIEWS)]
+    pub simulation_view: String,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub generation: GenerationArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub simulation: SimulationArgs,
+}
+
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct BenchmarkArgs {
+    /// Number of times to run optimization
+    #[clap(long, default_value_t = 20)]
+    pub runs: i32,
+
+    /// File to write the results and best candidate of every run to as CSV
+    #[clap(long)]
+    pub runs_file: Option<String>,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub report: ReportArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub generation: GenerationArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub simulation: SimulationArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub optimizer: OptimizerArgs,
+}
+
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct CompareArgs {
+    /// Optimizer configuration to compare given as comma separated parameter assignments on top
+    /// of the optimizer options, e.g. mutation=bitflip,population_size=20, at least two
+    #[clap(long = "variant", multiple_occurrences = true)]
+    pub variants: Vec<String>,
+
+    /// Number of seeded runs per configuration
+    #[clap(long, default_value_t = 20)]
+    pub runs: i32,
+
+    /// Draw a combined plot of the mean best values of each iteration of all configurations
+    #[clap(short, long)]
+    pub plot: bool,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub plot_options: PlotArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub generation: GenerationArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub simulation: SimulationArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub optimizer: OptimizerArgs,
+}
+
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct TuneArgs {
+    /// Number of configurations to sample from the parameter space for tuning
+    #[clap(long, default_value_t = 16)]
+    pub tune_configurations: usize,
+
+    /// Number of runs per configuration in the first round of tuning, doubled every round
+    #[clap(long, default_value_t = 2)]
+    pub tune_repetitions: usize,
+
+    /// Population sizes to tune, defaults to the population size
+    #[clap(long, use_value_delimiter = true)]
+    pub tune_population_sizes: Vec<usize>,
+
+    /// Parent population sizes to tune, defaults to the parent population size
+    #[clap(long, use_value_delimiter = true)]
+    pub tune_parents_sizes: Vec<usize>,
+
+    /// Tournament sizes to tune, defaults to the tournament size
+    #[clap(long, use_value_delimiter = true)]
+    pub tune_tournament_sizes: Vec<usize>,
+
+    /// Bitflip probabilities to tune, defaults to the bitflip probability
+    #[clap(long, use_value_delimiter = true)]
+    pub tune_probabilities_bitflip: Vec<f64>,
+
+    /// Recombination probabilities to tune, defaults to the recombination probability
+    #[clap(long, use_value_delimiter = true)]
+    pub tune_probabilities_recombination: Vec<f64>,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub generation: GenerationArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub simulation: SimulationArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub optimizer: OptimizerArgs,
+}
+
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct SweepArgs {
+    /// Parameter to sweep given as name=value1,value2,..., either an optimizer parameter like
+    /// population_size or a scenario parameter like intersections
+    #[clap(long, multiple_occurrences = true)]
+    pub grid: Vec<String>,
+
+    /// Scenario given as comma separated assignments of scenario parameters on top of the scenario
+    /// options, e.g. intersections=4,main_max_count=5, defaults to the scenario options only
+    #[clap(long = "scenario", multiple_occurrences = true)]
+    pub scenarios: Vec<String>,
+
+    /// Number of seeded runs per scenario and combination of grid values
+    #[clap(long, default_value_t = 5)]
+    pub repetitions: usize,
+
+    /// Number of runs to execute in parallel
+    #[clap(long, default_value_t = 1)]
+    pub threads: usize,
+
+    /// Write the results table as CSV to this file instead of stdout
+    #[clap(long)]
+    pub results_file: Option<String>,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub generation: GenerationArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub simulation: SimulationArgs,
+
+    #[clap(flatten)]
+    #[serde(flatten)]
+    pub optimizer: OptimizerArgs,
+}
+
+fn or_default<T>(values: &Vec<T>, default: T) -> Vec<T>
+where
+    T: Clone,
+{
+    if values.is_empty() {
+        return vec![default];
+    }
+    values.clone()
+}
+
+/// Configuration of a run without any output options set.
+pub fn build_configuration_data(seed: Option<u64>, data: &str) -> ConfigurationData {
+    ConfigurationData {
+        silent: false,
+        quiet: false,
+        print_final_simulation: false,
+        simulation_view: String::from("steps"),
+        benchmark: false,
+        benchmark_iterations: 0,
+        plot: false,
+        data: data.to_string(),
+        seed,
+        checkpoint: None,
+        checkpoint_interval: 0,
+        output: None,
+        output_file: None,
+        runs_file: None,
+        time_space_diagram: false,
+        platoons: false,
+        queue_heatmap: false,
+        baseline: None,
+        plot_dir: String::from("plots"),
+        plot_file: None,
+        plot_size: (800, 600),
+        plot_format: String::from("png"),
+    }
+}
+
+fn parse_plot_size(plot_size: &str) -> Result<(u32, u32), String> {
+    let size = plot_size
+        .split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+    match size {
+        Some((width, height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(format!(
+            "Invalid plot size {}, expected WIDTHxHEIGHT",
+            plot_size
+        )),
+    }
+}
+
+pub fn apply_plot_args(
+    configuration_data: &mut ConfigurationData,
+    args: &PlotArgs,
+) -> Result<(), String> {
+    configuration_data.plot_dir = args.plot_dir.clone();
+    configuration_data.plot_file = args.plot_file.clone();
+    configuration_data.plot_size = parse_plot_size(&args.plot_size)?;
+    configuration_data.plot_format = args.plot_format.clone();
+    Ok(())
+}
+
+/// Applies the report options, writing the run record to stdout hides all other output.
+pub fn apply_report_args(
+    configuration_data: &mut ConfigurationData,
+    args: &ReportArgs,
+) -> Result<(), String> {
+    let output_to_stdout = args.output.is_some() && args.output_file.is_none();
+    configuration_data.silent = args.silent || args.quiet || output_to_stdout;
+    configuration_data.quiet = args.quiet || output_to_stdout;
+    configuration_data.print_final_simulation = args.print_final_simulation;
+    configuration_data.simulation_view = args.simulation_view.clone();
+    configuration_data.plot = args.plot;
+    configuration_data.time_space_diagram = args.time_space_diagram;
+    configuration_data.platoons = args.platoons;
+    configuration_data.queue_heatmap = args.queue_heatmap;
+    configuration_data.baseline = args.baseline.clone();
+    configuration_data.output = args.output.clone();
+    configuration_data.output_file = args.output_file.clone();
+    apply_plot_args(configuration_data, &args.plot_options)
+}
+
+pub fn build_generation_data(args: &GenerationArgs) -> GenerationData {
+    GenerationData {
+        intersections: args.intersections,
+        timesteps: args.timesteps,
+        main_max_count: args.main_max_count,
+        side_max_count: args.side_max_count,
+        main_min_count: calculate_min_count(args.main_max_count),
+        side_min_count: calculate_min_count(args.side_max_count),
+        phases: vec![parse_phases(DEFAULT_PHASES).unwrap(); args.intersections],
+        phase_bits: 1,
+    }
+}
+
+pub fn build_traffic_data(
+    configuration_data: &ConfigurationData,
+    generation_data: &GenerationData,
+) -> Vec<Vec<TrafficState>> {
+    if configuration_data.data == "generate" {
+        let traffic_data = generate_data(generation_data);
+        if !configuration_data.quiet {
+            println!("{:?}", traffic_data);
+        }
+        traffic_data
+    } else {
+        fixed_data()
+    }
+}
+
+pub fn build_simulation_data(
+    args: &SimulationArgs,
+    traffic_data: Vec<Vec<TrafficState>>,
+) -> SimulationData {
+    SimulationData {
+        traffic_data,
+        disable_increasing_passthrough: args.disable_increasing_passthrough,
+        disable_max_passthrough: args.disable_max_passthrough,
+        max_passthroughs: Vec::new(),
+        timestep_length: args.timestep_length,
+        yellow_time: args.yellow_time,
+        all_red_time: args.all_red_time,
+        startup_lost_time: args.startup_lost_time,
+        link_capacity: args.link_capacity,
+        arrivals: args.arrivals.clone(),
+        sample_count: args.samples,
+        dispersion: args.dispersion,
+        robust_fitness: args.robust_fitness.clone(),
+        cvar_alpha: args.cvar_alpha,
+        samples: Vec::new(),
+        links: Vec::new(),
+        turn_shares: Vec::new(),
+    }
+}
+
+/// Seeds the generator and builds the traffic scenario all runs are simulated on.
+pub fn build_scenario(
+    configuration_data: &ConfigurationData,
+    generation_args: &GenerationArgs,
+    simulation_args: &SimulationArgs,
+) -> Result<(GenerationData, SimulationData), String> {
+    if let Some(seed) = configuration_data.seed {
+        seed_generator(seed);
+    }
+    if simulation_args.timestep_length <= 0.0 {
+        return Err(String::from("Timestep length must be positive"));
+    }
+    if simulation_args.yellow_time < 0.0
+        || simulation_args.all_red_time < 0.0
+        || simulation_args.startup_lost_time < 0.0
+    {
+        return Err(String::from(
+            "Yellow, all-red and start-up lost times must not be negative",
+        ));
+    }
+    if simulation_args
+        .link_capacity
+        .is_some_and(|capacity| capacity < 1)
+    {
+        return Err(String::from("Link capacity must be at least 1"));
+    }
+    if simulation_args.samples < 1 || simulation_args.dispersion <= 0.0 {
+        return Err(String::from(
+            "Stochastic arrivals need at least one sample and a positive dispersion",
+        ));
+    }
+    if simulation_args.cvar_alpha <= 0.0 || simulation_args.cvar_alpha > 1.0 {
+        return Err(String::from(
+            "CVaR alpha must be greater than 0 and at most 1",
+        ));
+    }
+    let phases = parse_phases(&simulation_args.phases)?;
+    let mut generation_data = build_generation_data(generation_args);
+    let traffic_data = build_traffic_data(configuration_data, &generation_data);
+    if traffic_data.len() != generation_data.intersections {
+        return Err(format!(
+            "Traffic data has {} intersections but {} are configured",
+            traffic_data.len(),
+            generation_data.intersections
+        ));
+    }
+    let mut simulation_data = build_simulation_data(simulation_args, traffic_data);
+    let max_passthrough = calculate_max_passthrough(generation_args.main_max_count);
+    let mut settings = build_intersection_settings(
+        generation_data.intersections,
+        &phases,
+        max_passthrough,
+        simulation_args.main_percentage,
+        simulation_args.side_percentage,
+    );
+    let mut network_links = None;
+    if let Some(path) = &simulation_args.network {
+        if simulation_args.main_percentage != DEFAULT_MAIN_PERCENTAGE
+            || simulation_args.side_percentage != DEFAULT_SIDE_PERCENTAGE
+            || simulation_args.rows != DEFAULT_ROWS
+        {
+            return Err(format!(
+                "Main percentage, side percentage and rows are given by the links of network {}",
+                path
+            ));
+        }
+        let network = load_network(path, &phases, max_passthrough)?;
+        if network.max_passthroughs.len() != generation_data.intersections {
+            return Err(format!(
+                "Network {} has {} intersections but {} are configured",
+                path,
+                network.max_passthroughs.len(),
+                generation_data.intersections
+            ));
+        }
+        for ((intersection, phases), max_passthrough) in settings
+            .iter_mut()
+            .zip(network.phases)
+            .zip(network.max_passthroughs)
+        {
+            intersection.phases = phases;
+            intersection.max_passthrough = max_passthrough;
+        }
+        network_links = Some(network.links);
+    }
+    apply_intersection_settings(
+        &mut settings,
+        &simulation_args.intersection_settings,
+        network_links.is_none(),
+    )?;
+    check_intersection_settings(&settings)?;
+    generation_data.phases = settings
+        .iter()
+        .map(|intersection| intersection.phases.clone())
+        .collect();
+    generation_data.phase_bits = generation_data
+        .phases
+        .iter()
+        .map(|phases| get_phase_bits(phases.len()))
+        .max()
+        .unwrap_or(1);
+    simulation_data.max_passthroughs = settings
+        .iter()
+        .map(|intersection| intersection.max_passthrough.clone())
+        .collect();
+    simulation_data.turn_shares = match &network_links {
+        Some(links) => get_link_turn_shares(generation_data.intersections, links),
+        None => get_turn_shares(&settings),
+    };
+    simulation_data.links = match network_links {
+        Some(links) => links,
+        None => build_grid_links(simulation_args.rows, &settings)?,
+    };
+    for link in simulation_data.links.iter_mut() {
+        link.capacity = link.capacity.or(simulation_args.link_capacity);
+    }
+    simulation_data.samples = sample_realizations(&simulation_data);
+    Ok((generation_data, simulation_data))
+}
+
+pub fn build_optimization_data(
+    args: &OptimizerArgs,
+    generation_data: &GenerationData,
+) -> Result<OptimizationData, String> {
+    if args.sharing_radius <= 0.0 {
+        return Err(String::from("Sharing radius must be positive"));
+    }
+    if !(0.0..=1.0).contains(&args.seed_fraction) {
+        return Err(String::from("Seed fraction must be between 0 and 1"));
+    }
+    let optimization_data = OptimizationData {
+        iterations: args.iterations,
+        optimization: args.optimization.clone(),
+        mutation: args.mutation.clone(),
+        recombination: args.recombination.clone(),
+        adaptive_strategy: args.adaptive_strategy.clone(),
+        probability_bitflip: args.probability_bitflip,
+        probability_recombination: args.probability_recombination,
+        population_size: args.population_size,
+        parents_size: args.parents_size,
+        tournament_size: args.tournament_size,
+        fitness_value: args.fitness_value.clone(),
+        replacement: args.replacement.clone(),
+        fitness_sharing: args.fitness_sharing,
+        sharing_radius: args.sharing_radius,
+        stagnation_iterations: args.stagnation_iterations,
+        target_value: args.target_value,
+        time_limit: args.time_limit,
+        max_evaluations: args.max_evaluations,
+        seed_fraction: args.seed_fraction,
+        seed_candidates: generate_seeds(
+            &args.seeding,
+            &args.seed_cycle_lengths,
+            &args.seed_file,
+            generation_data,
+        )?,
+    };
+    check_optimization_data(&optimization_data)?;
+    Ok(optimization_data)
+}
+
+pub fn build_tuning_data(
+    args: &TuneArgs,
+    optimization_data: &OptimizationData,
+) -> Result<TuningData, String> {
+    if args.tune_configurations < 1 || args.tune_repetitions < 1 {
+        return Err(String::from(
+            "Tuning needs at least one configuration and one repetition",
+        ));
+    }
+    Ok(TuningData {
+        configurations: args.tune_configurations,
+        repetitions: args.tune_repetitions,
+        population_sizes: or_default(
+            &args.tune_population_sizes,
+            optimization_data.population_size,
+        ),
+        parents_sizes: or_default(&args.tune_parents_sizes, optimization_data.parents_size),
+        tournament_sizes: or_default(
+            &args.tune_tournament_sizes,
+            optimization_data.tournament_size,
+        ),
+        probabilities_bitflip: or_default(
+            &args.tune_probabilities_bitflip,
+            optimization_data.probability_bitflip,
+        ),
+        probabilities_recombination: or_default(
+            &args.tune_probabilities_recombination,
+            optimization_data.probability_recombination,
+        ),
+    })
+}
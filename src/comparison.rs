@@ -0,0 +1,139 @@
+use crate::data::ConfigurationData;
+use crate::data::GenerationData;
+use crate::data::OptimizationData;
+use crate::data::PlotData;
+use crate::data::SimulationData;
+use crate::optimization::optimize;
+use crate::parameters::{apply_assignments, check_optimization_data};
+use crate::plot::draw_comparison_plot;
+use crate::statistics::effect_size_magnitude;
+use crate::statistics::get_convergence_values;
+use crate::statistics::get_median;
+use crate::statistics::get_standard_deviation;
+use crate::statistics::mann_whitney_u;
+use crate::statistics::vargha_delaney_a12;
+use crate::statistics::wilcoxon_signed_rank;
+use crate::utils::get_mean_value;
+use crate::utils::seed_generator;
+
+pub struct Variant {
+    pub name: String,
+    pub optimization_data: OptimizationData,
+    pub values: Vec<f64>,
+    pub best_values: Vec<Vec<f64>>,
+}
+
+pub fn build_variants(
+    specifications: &Vec<String>,
+    optimization_data: &OptimizationData,
+) -> Result<Vec<Variant>, String> {
+    if specifications.len() < 2 {
+        return Err(String::from("Comparing needs at least two variants"));
+    }
+    let mut variants: Vec<Variant> = Vec::with_capacity(specifications.len());
+    for specification in specifications.iter() {
+        let mut variant_optimization_data = optimization_data.clone();
+        apply_assignments(&mut variant_optimization_data, specification)?;
+        check_optimization_data(&variant_optimization_data)
+            .map_err(|error| format!("Variant {}: {}", specification, error))?;
+        variants.push(Variant {
+            name: specification.clone(),
+            optimization_data: variant_optimization_data,
+            values: Vec::new(),
+            best_values: Vec::new(),
+        });
+    }
+    Ok(variants)
+}
+
+/// Mean best value per iteration over all runs, runs stopped early keep their last best value.
+pub fn get_mean_convergence(best_values: &Vec<Vec<f64>>) -> Vec<f64> {
+    get_convergence_values(best_values)
+        .iter()
+        .map(get_mean_value)
+        .collect()
+}
+
+fn print_pairwise_tests(variants: &Vec<Variant>) {
+    println!("Pairwise comparisons:");
+    println!("a\tb\tmann_whitney_p\twilcoxon_p\ta12\teffect");
+    for index1 in 0..variants.len() {
+        for index2 in (index1 + 1)..variants.len() {
+            let values1 = &variants[index1].values;
+            let values2 = &variants[index2].values;
+            let (_, mann_whitney_p) = mann_whitney_u(values1, values2);
+            let (_, wilcoxon_p) = wilcoxon_signed_rank(values1, values2);
+            let a12 = vargha_delaney_a12(values1, values2);
+            println!(
+                "{}\t{}\t{:.4}\t{:.4}\t{:.3}\t{}",
+                variants[index1].name,
+                variants[index2].name,
+                mann_whitney_p,
+                wilcoxon_p,
+                a12,
+                effect_size_magnitude(a12)
+            );
+        }
+    }
+}
+
+/// Runs every variant once per run, all variants use the same seed in a run so they start from
+/// the same random state and the runs can be compared pairwise.
+pub fn compare(
+    configuration_data: &ConfigurationData,
+    variants: &mut Vec<Variant>,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    runs: i32,
+    seed: u64,
+) -> Result<(), String> {
+    if runs < 1 {
+        return Err(String::from("Comparing needs at least one run"));
+    }
+    for run in 0..runs {
+        for variant in variants.iter_mut() {
+            seed_generator(seed + run as u64);
+            let mut plot_data = PlotData::default();
+            let result = optimize(
+                configuration_data,
+                &variant.optimization_data,
+                simulation_data,
+                generation_data,
+                &mut plot_data,
+            );
+            variant.values.push(result.best_value);
+            variant.best_values.push(plot_data.best_values);
+        }
+    }
+
+    println!("Results over {} runs with seeds from {}:", runs, seed);
+    println!("mean\tstd\tmedian\tvariant");
+    for variant in variants.iter() {
+        let standard_deviation = match get_standard_deviation(&variant.values) {
+            Some(standard_deviation) => format!("{:.4}", standard_deviation),
+            None => String::from("n/a"),
+        };
+        println!(
+            "{:.4}\t{}\t{:.4}\t{}",
+            get_mean_value(&variant.values),
+            standard_deviation,
+            get_median(&variant.values),
+            variant.name
+        );
+    }
+    print_pairwise_tests(variants);
+
+    if configuration_data.plot {
+        let curves: Vec<(String, Vec<f64>)> = variants
+            .iter()
+            .map(|variant| {
+                (
+                    variant.name.clone(),
+                    get_mean_convergence(&variant.best_values),
+                )
+            })
+            .collect();
+        draw_comparison_plot(configuration_data, &curves)?;
+    }
+    Ok(())
+}
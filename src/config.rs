@@ -0,0 +1,160 @@
+use clap::{Arg, ArgMatches, Command};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+
+fn load_config(path: &str) -> Result<serde_json::Map<String, Value>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|error| format!("Could not read configuration from {}: {}", path, error))?;
+    let config: toml::Value = toml::from_str(&content)
+        .map_err(|error| format!("Could not parse configuration {}: {}", path, error))?;
+    match serde_json::to_value(config) {
+        Ok(Value::Object(map)) => Ok(map),
+        _ => Err(format!("Could not parse configuration {}", path)),
+    }
+}
+
+/// Values from a file bypass the command line parser, so choices are checked against the
+/// possible values of the argument here.
+fn check_possible_values(command: &Command, id: &str, value: &Value) -> Result<(), String> {
+    let possible_values = match command
+        .get_arguments()
+        .find(|arg| arg.get_id() == id)
+        .and_then(|arg| arg.get_possible_values())
+    {
+        Some(possible_values) => possible_values,
+        None => return Ok(()),
+    };
+    let values = match value {
+        Value::Array(values) => values.clone(),
+        _ => vec![value.clone()],
+    };
+    for value in values.iter() {
+        let valid = match value.as_str() {
+            Some(text) => possible_values
+                .iter()
+                .any(|possible_value| possible_value.get_name() == text),
+            None => false,
+        };
+        if !valid {
+            let names: Vec<&str> = possible_values
+                .iter()
+                .map(|possible_value| possible_value.get_name())
+                .collect();
+            return Err(format!(
+                "Invalid value {} for {}, possible values: {}",
+                value,
+                id,
+                names.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Key of an option in configuration files, its long name in snake case.
+fn config_key(arg: &Arg) -> String {
+    arg.get_long()
+        .unwrap_or_else(|| arg.get_id())
+        .replace('-', "_")
+}
+
+/// Name of the field an argument is stored in, arguments are named like their fields in kebab
+/// case.
+fn field_name(arg: &Arg) -> String {
+    arg.get_id().replace('-', "_")
+}
+
+/// Whether any subcommand has an option with the key.
+fn is_known_key(root: &Command, key: &str) -> bool {
+    root.get_arguments()
+        .chain(
+            root.get_subcommands()
+                .flat_map(|subcommand| subcommand.get_arguments()),
+        )
+        .any(|arg| config_key(arg) == key)
+}
+
+/// Replaces all options not given on the command line with the values of the configuration file.
+/// Options of other subcommands are ignored with a warning, so one file can be shared between
+/// subcommands, options unknown to all subcommands are an error.
+pub fn apply_config<T: Serialize + DeserializeOwned>(
+    args: T,
+    source: &ConfigSource,
+) -> Result<T, String> {
+    let path = match &source.config {
+        Some(path) => path,
+        None => return Ok(args),
+    };
+    let config = load_config(path)?;
+
+    let mut values = match serde_json::to_value(&args) {
+        Ok(Value::Object(values)) => values,
+        _ => return Err(String::from("Could not apply configuration")),
+    };
+    for (key, value) in config.into_iter() {
+        let arg = source
+            .command
+            .get_arguments()
+            .find(|arg| config_key(arg) == key)
+            .filter(|arg| values.contains_key(&field_name(arg)));
+        let arg = match arg {
+            Some(arg) => arg,
+            None if is_known_key(source.root, &key) => {
+                eprintln!("Ignoring option {} of {} not used here", key, path);
+                continue;
+            }
+            None => return Err(format!("Unknown option {} in {}", key, path)),
+        };
+        if source.matches.occurrences_of(arg.get_id()) == 0 {
+            check_possible_values(source.command, arg.get_id(), &value)
+                .map_err(|error| format!("{} in {}", error, path))?;
+            values.insert(field_name(arg), value);
+        }
+    }
+    serde_json::from_value(Value::Object(values))
+        .map_err(|error| format!("Invalid configuration {}: {}", path, error))
+}
+
+/// Writes the options under the same keys configuration files are read with.
+pub fn dump_config<T: Serialize>(args: &T, command: &Command, path: &str) -> Result<(), String> {
+    let values = match serde_json::to_value(args) {
+        Ok(Value::Object(values)) => values,
+        _ => return Err(String::from("Could not serialize configuration")),
+    };
+    let mut config = serde_json::Map::new();
+    for arg in command.get_arguments() {
+        match values.get(&field_name(arg)) {
+            Some(Value::Null) | None => {}
+            Some(value) => {
+                config.insert(config_key(arg), value.clone());
+            }
+        }
+    }
+    let content = toml::to_string(&Value::Object(config))
+        .map_err(|error| format!("Could not serialize configuration: {}", error))?;
+    fs::write(path, content)
+        .map_err(|error| format!("Could not write configuration to {}: {}", path, error))
+}
+
+/// Where the options of a subcommand come from besides its parsed arguments.
+pub struct ConfigSource<'a> {
+    pub root: &'a Command<'static>,
+    pub command: &'a Command<'static>,
+    pub matches: &'a ArgMatches,
+    pub config: Option<String>,
+    pub dump_config: Option<String>,
+}
+
+/// Applies the configuration file and writes the effective configuration if requested.
+pub fn configure<T: Serialize + DeserializeOwned>(
+    args: T,
+    source: &ConfigSource,
+) -> Result<T, String> {
+    let args = apply_config(args, source)?;
+    if let Some(path) = &source.dump_config {
+        dump_config(&args, source.command, path)?;
+    }
+    Ok(args)
+}
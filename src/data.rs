@@ -1,19 +1,36 @@
+use crate::utils::generator;
 use bit_vec::BitVec;
-use rand::random;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigurationData {
     pub silent: bool,
+    pub quiet: bool,
     pub print_final_simulation: bool,
+    pub simulation_view: String,
     pub benchmark: bool,
     pub benchmark_iterations: i32,
     pub plot: bool,
     pub data: String,
+    pub seed: Option<u64>,
+    pub checkpoint: Option<String>,
+    pub checkpoint_interval: usize,
+    pub output: Option<String>,
+    pub output_file: Option<String>,
+    pub runs_file: Option<String>,
+    pub time_space_diagram: bool,
+    pub platoons: bool,
+    pub queue_heatmap: bool,
+    pub baseline: Option<String>,
+    pub plot_dir: String,
+    pub plot_file: Option<String>,
+    pub plot_size: (u32, u32),
+    pub plot_format: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenerationData {
     pub intersections: usize,
     pub timesteps: usize,
@@ -21,33 +38,99 @@ pub struct GenerationData {
     pub side_max_count: i32,
     pub main_min_count: i32,
     pub side_min_count: i32,
+    pub phases: Vec<Vec<Phase>>,
+    pub phase_bits: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimulationData {
     pub traffic_data: Vec<Vec<TrafficState>>,
     pub disable_increasing_passthrough: bool,
     pub disable_max_passthrough: bool,
-    pub max_passthrough: i32,
-    pub main_percentage: f64,
-    pub side_percentage: f64,
+    pub max_passthroughs: Vec<TrafficState>,
+    pub timestep_length: f64,
+    pub yellow_time: f64,
+    pub all_red_time: f64,
+    pub startup_lost_time: f64,
+    pub link_capacity: Option<i32>,
+    pub arrivals: String,
+    pub sample_count: usize,
+    pub dispersion: f64,
+    pub robust_fitness: String,
+    pub cvar_alpha: f64,
+    pub samples: Vec<Vec<Vec<TrafficState>>>,
+    pub links: Vec<Link>,
+    pub turn_shares: Vec<Vec<f64>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptimizationData {
     pub iterations: usize,
     pub optimization: String,
     pub mutation: String,
     pub recombination: String,
+    pub adaptive_strategy: String,
     pub probability_bitflip: f64,
     pub probability_recombination: f64,
     pub population_size: usize,
     pub parents_size: usize,
     pub tournament_size: usize,
     pub fitness_value: String,
+    pub replacement: String,
+    pub fitness_sharing: bool,
+    pub sharing_radius: f64,
+    pub stagnation_iterations: Option<usize>,
+    pub target_value: Option<f64>,
+    pub time_limit: Option<f64>,
+    pub max_evaluations: Option<usize>,
+    pub seed_fraction: f64,
+    #[serde(skip)]
+    pub seed_candidates: Vec<Vec<BitVec>>,
+}
+
+#[derive(Debug)]
+pub struct TuningData {
+    pub configurations: usize,
+    pub repetitions: usize,
+    pub population_sizes: Vec<usize>,
+    pub parents_sizes: Vec<usize>,
+    pub tournament_sizes: Vec<usize>,
+    pub probabilities_bitflip: Vec<f64>,
+    pub probabilities_recombination: Vec<f64>,
+}
+
+#[derive(Debug)]
+pub struct OptimizationResult {
+    pub best_candidate: Vec<BitVec>,
+    pub best_value: f64,
+    pub iterations: usize,
+    pub evaluations: usize,
+    pub duration: f64,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlotData {
+    pub best_values: Vec<f64>,
+    pub mean_values: Vec<f64>,
+    pub worst_values: Vec<f64>,
+    pub diversity_values: Vec<f64>,
+    pub entropy_values: Vec<f64>,
+    pub evaluations: Vec<usize>,
+    pub durations: Vec<f64>,
+}
+
+/// Traffic of every simulation step and the cars that passed each intersection in every step.
+#[derive(Debug, Clone)]
+pub struct SimulationTrace {
+    pub states: Vec<Vec<TrafficState>>,
+    pub passed: Vec<Vec<TrafficState>>,
+    pub blocked: Vec<i32>,
+    pub gridlock_step: Option<usize>,
+    pub driving_cars: i32,
+    pub waiting_cars: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TrafficState {
     pub main_from_prev: i32,
     pub main_from_next: i32,
@@ -64,8 +147,29 @@ impl fmt::Debug for TrafficState {
     }
 }
 
+/// Road from an approach of one intersection to an approach of another, taking the share
+/// `ratio` of the cars passing the first approach. Cars arrive after `travel_time` timesteps and
+/// no more cars enter than `capacity` places are left in the link and the approach it leads to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Link {
+    pub from: usize,
+    pub from_approach: usize,
+    pub to: usize,
+    pub to_approach: usize,
+    pub turn: usize,
+    pub ratio: f64,
+    pub capacity: Option<i32>,
+    pub travel_time: usize,
+}
+
+/// Turns an intersection gives green at the same time, numbered like `TURNS`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Phase {
+    pub turns: Vec<bool>,
+}
+
 fn random_traffic_value(generation_data: &GenerationData, main: bool) -> i32 {
-    let mut rng = rand::thread_rng();
+    let mut rng = generator();
     if main {
         return rng.gen_range(generation_data.main_min_count..generation_data.main_max_count);
     }
@@ -124,6 +228,15 @@ pub fn calculate_increased_max_passthrough(max: i32) -> i32 {
     ((max as f64) * 1.4).round() as i32
 }
 
+/// Share of a timestep left for green after the yellow and all-red clearance of the previous
+/// phase and the start-up lost time of the next phase.
+pub fn calculate_green_share(simulation_data: &SimulationData) -> f64 {
+    let lost_time = simulation_data.yellow_time
+        + simulation_data.all_red_time
+        + simulation_data.startup_lost_time;
+    ((simulation_data.timestep_length - lost_time) / simulation_data.timestep_length).max(0.0)
+}
+
 pub fn generate_data(generation_data: &GenerationData) -> Vec<Vec<TrafficState>> {
     let mut data: Vec<Vec<TrafficState>> = Vec::with_capacity(generation_data.intersections);
     for index in 0..generation_data.intersections {
@@ -147,26 +260,87 @@ pub fn generate_data(generation_data: &GenerationData) -> Vec<Vec<TrafficState>>
     data
 }
 
-pub fn generate_candidate(intersections: usize, timesteps: usize) -> Vec<BitVec> {
+pub fn generate_candidate(intersections: usize, bits: usize) -> Vec<BitVec> {
+    let mut rng = generator();
     let mut candidate: Vec<BitVec> = Vec::with_capacity(intersections);
     for _ in 0..intersections {
-        let mut data = BitVec::with_capacity(timesteps);
-        for _ in 0..timesteps {
-            data.push(random())
+        let mut data = BitVec::with_capacity(bits);
+        for _ in 0..bits {
+            data.push(rng.gen())
         }
         candidate.push(data);
     }
     candidate
 }
 
+pub fn format_candidate(candidate: &Vec<BitVec>) -> String {
+    candidate
+        .iter()
+        .map(|data| {
+            data.iter()
+                .map(|bit| if bit { '1' } else { '0' })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Parses a candidate from bit strings per intersection, separated by commas, whitespace or
+/// line breaks. Brackets are ignored, so the printed form of a candidate can be used as well.
+pub fn parse_bits(text: &str) -> Result<Vec<BitVec>, String> {
+    let mut candidate: Vec<BitVec> = Vec::new();
+    for part in text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .map(|part| part.trim_matches(|c| c == '[' || c == ']'))
+        .filter(|part| !part.is_empty())
+    {
+        let mut data = BitVec::with_capacity(part.len());
+        for c in part.chars() {
+            match c {
+                '1' => data.push(true),
+                '0' => data.push(false),
+                _ => return Err(format!("Invalid character '{}' in candidate", c)),
+            }
+        }
+        candidate.push(data);
+    }
+    Ok(candidate)
+}
+
+/// Parses a candidate like `parse_bits` and checks that it fits the simulation dimensions.
+pub fn parse_candidate(
+    text: &str,
+    intersections: usize,
+    bits: usize,
+) -> Result<Vec<BitVec>, String> {
+    let candidate = parse_bits(text)?;
+    if candidate.len() != intersections {
+        return Err(format!(
+            "Expected {} intersections but got {}",
+            intersections,
+            candidate.len()
+        ));
+    }
+    for data in candidate.iter() {
+        if data.len() != bits {
+            return Err(format!(
+                "Expected {} bits per intersection but got {}",
+                bits,
+                data.len()
+            ));
+        }
+    }
+    Ok(candidate)
+}
+
 pub fn generate_population(
     population_size: usize,
     intersections: usize,
-    timesteps: usize,
+    bits: usize,
 ) -> Vec<Vec<BitVec>> {
     let mut population: Vec<Vec<BitVec>> = Vec::with_capacity(population_size);
     for _ in 0..population_size {
-        let candidate = generate_candidate(intersections, timesteps);
+        let candidate = generate_candidate(intersections, bits);
         population.push(candidate);
     }
     population
@@ -0,0 +1,83 @@
+use crate::data::OptimizationData;
+use bit_vec::BitVec;
+
+pub fn hamming_distance(candidate1: &Vec<BitVec>, candidate2: &Vec<BitVec>) -> usize {
+    let mut distance = 0;
+    for (bitvec1, bitvec2) in candidate1.iter().zip(candidate2.iter()) {
+        distance += bitvec1
+            .iter()
+            .zip(bitvec2.iter())
+            .filter(|(bit1, bit2)| bit1 != bit2)
+            .count();
+    }
+    distance
+}
+
+pub fn candidate_bits(candidate: &Vec<BitVec>) -> usize {
+    candidate.iter().map(|bitvec| bitvec.len()).sum()
+}
+
+pub fn get_mean_pairwise_distance(population: &Vec<Vec<BitVec>>) -> f64 {
+    let mut distance_sum = 0;
+    let mut pairs = 0;
+    for i in 0..population.len() {
+        for j in i + 1..population.len() {
+            distance_sum += hamming_distance(&population[i], &population[j]);
+            pairs += 1;
+        }
+    }
+    if pairs == 0 {
+        return 0.0;
+    }
+    distance_sum as f64 / pairs as f64
+}
+
+/// Mean binary entropy over all bit positions of the population, between 0 and 1.
+pub fn get_mean_bit_entropy(population: &Vec<Vec<BitVec>>) -> f64 {
+    if population.is_empty() {
+        return 0.0;
+    }
+    let mut entropy_sum = 0.0;
+    let mut positions = 0;
+    for index1 in 0..population[0].len() {
+        for index2 in 0..population[0][index1].len() {
+            let ones = population
+                .iter()
+                .filter(|candidate| candidate[index1][index2])
+                .count();
+            let probability = ones as f64 / population.len() as f64;
+            if probability > 0.0 && probability < 1.0 {
+                entropy_sum -= probability * probability.log2()
+                    + (1.0 - probability) * (1.0 - probability).log2();
+            }
+            positions += 1;
+        }
+    }
+    if positions == 0 {
+        return 0.0;
+    }
+    entropy_sum / positions as f64
+}
+
+/// Divides the values by the niche count of each candidate. Values are shifted to be positive
+/// first, so the sharing also works for fitness values that can become negative.
+pub fn get_shared_values(
+    population: &Vec<Vec<BitVec>>,
+    population_values: &Vec<f64>,
+    optimization_data: &OptimizationData,
+) -> Vec<f64> {
+    let lowest = population_values.iter().cloned().fold(f64::MAX, f64::min);
+    let mut shared_values = Vec::with_capacity(population_values.len());
+
+    for i in 0..population.len() {
+        let mut niche_count = 0.0;
+        for j in 0..population.len() {
+            let distance = hamming_distance(&population[i], &population[j]) as f64;
+            if distance < optimization_data.sharing_radius {
+                niche_count += 1.0 - distance / optimization_data.sharing_radius;
+            }
+        }
+        shared_values.push((population_values[i] - lowest + f64::EPSILON) / niche_count);
+    }
+    shared_values
+}
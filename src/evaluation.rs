@@ -0,0 +1,102 @@
+use crate::arrivals::get_robust_value;
+use crate::arrivals::ROBUST_FITNESS;
+use crate::data::parse_candidate;
+use crate::data::GenerationData;
+use crate::data::SimulationData;
+use crate::network::has_link_capacities;
+use crate::phases::check_plan;
+use crate::render::print_simulation;
+use crate::seeding::load_candidates;
+use crate::simulation::fitness;
+use crate::simulation::simulate_samples;
+use crate::simulation::trace_simulation;
+use crate::simulation::FITNESS_VALUES;
+use bit_vec::BitVec;
+use std::path::Path;
+
+/// Reads the plans to evaluate, the plan is either a file with plans separated by empty lines
+/// or a single plan given as bit strings separated by commas. Every plan must only show existing
+/// phases.
+pub fn load_plans(
+    plan: &str,
+    generation_data: &GenerationData,
+) -> Result<Vec<Vec<BitVec>>, String> {
+    let candidates = if Path::new(plan).is_file() {
+        let candidates = load_candidates(
+            plan,
+            generation_data.intersections,
+            generation_data.timesteps * generation_data.phase_bits,
+        )?;
+        if candidates.is_empty() {
+            return Err(format!("No plan found in {}", plan));
+        }
+        candidates
+    } else {
+        vec![parse_candidate(
+            plan,
+            generation_data.intersections,
+            generation_data.timesteps * generation_data.phase_bits,
+        )?]
+    };
+    for candidate in candidates.iter() {
+        check_plan(candidate, generation_data)?;
+    }
+    Ok(candidates)
+}
+
+pub fn evaluate(
+    plans: &Vec<Vec<BitVec>>,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    simulation_view: Option<&str>,
+) {
+    for (index, plan) in plans.iter().enumerate() {
+        if plans.len() > 1 {
+            println!("Plan {}:", index + 1);
+        }
+        if let Some(view) = simulation_view {
+            print_simulation(plan, simulation_data, generation_data, view);
+        }
+        let trace = trace_simulation(plan, simulation_data, generation_data);
+        let (driving_cars, waiting_cars) = (trace.driving_cars, trace.waiting_cars);
+        println!("{:?}", plan);
+        println!("driving_cars: {}", driving_cars);
+        println!("waiting_cars: {}", waiting_cars);
+        if has_link_capacities(&simulation_data.links) {
+            println!("blocked_cars: {}", trace.blocked.iter().sum::<i32>());
+            match trace.gridlock_step {
+                Some(t) => println!("gridlock: step {}", t),
+                None => println!("gridlock: none"),
+            }
+        }
+        for fitness_value in FITNESS_VALUES.iter() {
+            println!(
+                "fitness {}: {:.4}",
+                fitness_value,
+                fitness(fitness_value, driving_cars, waiting_cars)
+            );
+        }
+        if !simulation_data.samples.is_empty() {
+            for fitness_value in FITNESS_VALUES.iter() {
+                let values =
+                    simulate_samples(plan, simulation_data, generation_data, fitness_value);
+                let robust_values: Vec<String> = ROBUST_FITNESS
+                    .iter()
+                    .map(|robust_fitness| {
+                        format!(
+                            "{} {:.4}",
+                            robust_fitness,
+                            get_robust_value(&values, robust_fitness, simulation_data.cvar_alpha)
+                        )
+                    })
+                    .collect();
+                println!(
+                    "fitness {} over {} samples: {}",
+                    fitness_value,
+                    values.len(),
+                    robust_values.join(", ")
+                );
+            }
+        }
+    }
+}
@@ -1,252 +1,336 @@
 use crate::data::{
-    calculate_max_passthrough, calculate_min_count, fixed_data, generate_data, ConfigurationData,
-    GenerationData, OptimizationData, SimulationData,
+    generate_data, ConfigurationData, GenerationData, OptimizationData, PlotData, SimulationData,
 };
-use chrono::{DateTime, Local};
-use clap::Parser;
-use optimization::optimize;
-use plotters::prelude::{
-    BitMapBackend, ChartBuilder, IntoDrawingArea, LabelAreaPosition, LineSeries,
+use benchmark::benchmark;
+use checkpoint::load_checkpoint;
+use clap::{CommandFactory, FromArgMatches};
+use cli::{
+    apply_plot_args, apply_report_args, build_configuration_data, build_generation_data,
+    build_optimization_data, build_scenario, build_tuning_data, BenchmarkArgs, Cli, Command,
+    CompareArgs, EvaluateArgs, GenerateArgs, OptimizeArgs, SweepArgs, TuneArgs,
 };
-use plotters::style::{BLUE, WHITE};
-use std::time::Instant;
-use utils::get_highest_and_lowest;
-
+use comparison::{build_variants, compare};
+use config::{configure, ConfigSource};
+use evaluation::{evaluate, load_plans};
+use optimization::{optimize, resume};
+use output::{build_run_record, write_output, OutputRecord, RunRecord};
+use plot::{draw_plot, draw_queue_heatmaps, draw_time_space_diagram};
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use simulation::trace_simulation;
+use std::process;
+use sweep::{parse_grid, sweep, SweepData};
+use tuning::tune;
+use utils::{generator, seed_generator};
+
+pub mod adaptive;
+pub mod arrivals;
+pub mod benchmark;
+pub mod checkpoint;
+pub mod cli;
+pub mod comparison;
+pub mod config;
 pub mod data;
+pub mod diversity;
+pub mod evaluation;
+pub mod network;
 pub mod optimization;
+pub mod output;
+pub mod parameters;
+pub mod phases;
+pub mod plot;
+pub mod render;
+pub mod seeding;
 pub mod simulation;
+pub mod statistics;
+pub mod sweep;
+pub mod termination;
+pub mod tuning;
 pub mod utils;
 
-/// Evolutionary algorithm to optimize traffic lights on a linear road with intersections
-#[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
-struct Args {
-    /// Hide output on iterations with improvements
-    #[clap(short, long)]
-    silent: bool,
-
-    /// Print the simulation data for the final best candidate
-    #[clap(long)]
-    print_final_simulation: bool,
-
-    /// Run optimization a set amount of times (default 20) and show mean of results
-    #[clap(short, long)]
-    benchmark: bool,
-
-    /// Number of times to run optimization in benchmark
-    #[clap(long, default_value_t = 20)]
-    benchmark_iterations: i32,
-
-    /// Draw plot of best values of each iteration
-    #[clap(short, long)]
-    plot: bool,
-
-    /// Car traffic data to use for the traffic simulation
-    #[clap(short, long, default_value = "fixed", possible_values = ["fixed", "generate"])]
-    data: String,
-
-    /// Maximum number of cars possible on the main road
-    #[clap(long, default_value_t = 20)]
-    main_max_count: i32,
-
-    /// Maximum number of cars possible on the side roads
-    #[clap(long, default_value_t = 10)]
-    side_max_count: i32,
-
-    /// Number of iterations to run
-    #[clap(short, long, default_value_t = 1000)]
-    iterations: usize,
-
-    /// Optimization variant to use
-    #[clap(short, long, default_value = "genetic", possible_values = ["genetic", "hillclimb"])]
-    optimization: String,
-
-    /// Mutation variant to use
-    #[clap(short, long, default_value = "prob_bitflip", possible_values = ["none", "bitflip", "prob_bitflip"])]
-    mutation: String,
-
-    /// Mutation variant to use
-    #[clap(short, long, default_value = "two_point", possible_values = ["one_point", "two_point"])]
-    recombination: String,
-
-    /// Probability for bitflip in prob_bitflip mutation
-    #[clap(long, default_value_t = 0.0078125)]
-    probability_bitflip: f64,
-
-    /// Probability for bitflip in prob_bitflip mutation
-    #[clap(long, default_value_t = 0.75)]
-    probability_recombination: f64,
-
-    /// Population size
-    #[clap(long, default_value_t = 50)]
-    population_size: usize,
-
-    /// Parent population size
-    #[clap(long, default_value_t = 10)]
-    parents_size: usize,
-
-    /// Tournament size
-    #[clap(long, default_value_t = 5)]
-    tournament_size: usize,
-
-    /// Fitness value to use during optimization
-    #[clap(long, default_value = "ratio", possible_values = ["ratio", "difference", "driving_cars", "waiting_cars"])]
-    fitness_value: String,
-
-    /// Number of intersections for the traffic simulation
-    #[clap(long, default_value_t = 8)]
-    intersections: usize,
-
-    /// Number of timesteps for the traffic simulation
-    #[clap(long, default_value_t = 16)]
-    timesteps: usize,
-
-    /// Disable the increasing passthrough to keep max passthrough always the same
-    #[clap(long)]
-    disable_increasing_passthrough: bool,
-
-    /// Disable the max passthrough value to not limit cars per timestep
-    #[clap(long)]
-    disable_max_passthrough: bool,
-
-    /// Amount of cars staying on the main road
-    #[clap(long, default_value_t = 0.8)]
-    main_percentage: f64,
-
-    /// Amount of cars coming to main road from side roads
-    #[clap(long, default_value_t = 0.6)]
-    side_percentage: f64,
+fn exit_with_error(error: &str) -> ! {
+    eprintln!("{}", error);
+    process::exit(1)
 }
 
-fn main() {
-    let args = Args::parse();
-
-    let configuration_data = ConfigurationData {
-        silent: args.silent,
-        print_final_simulation: args.print_final_simulation,
-        benchmark: args.benchmark,
-        benchmark_iterations: args.benchmark_iterations,
-        plot: args.plot,
-        data: args.data,
+fn finish_runs(
+    configuration_data: &ConfigurationData,
+    generation_data: &GenerationData,
+    optimization_data: &OptimizationData,
+    simulation_data: &SimulationData,
+    runs: &Vec<RunRecord>,
+    plot_data: &PlotData,
+) {
+    // Benchmarks draw their plot over all runs themselves
+    if configuration_data.plot && !configuration_data.benchmark {
+        draw_plot(configuration_data, plot_data, optimization_data)
+            .unwrap_or_else(|error| exit_with_error(&error));
+    }
+    let best_run = runs
+        .iter()
+        .max_by(|run1, run2| run1.best_value.total_cmp(&run2.best_value));
+    if let Some(best_run) = best_run {
+        let trace = trace_simulation(&best_run.best_candidate, simulation_data, generation_data);
+        if configuration_data.time_space_diagram {
+            draw_time_space_diagram(
+                configuration_data,
+                optimization_data,
+                generation_data,
+                simulation_data,
+                &best_run.best_candidate,
+                &trace,
+            )
+            .unwrap_or_else(|error| exit_with_error(&error));
+        }
+        if configuration_data.queue_heatmap {
+            let mut traces = vec![(String::from("best"), trace)];
+            if let Some(baseline) = &configuration_data.baseline {
+                let plans = load_plans(baseline, generation_data)
+                    .unwrap_or_else(|error| exit_with_error(&error));
+                traces.push((
+                    String::from("baseline"),
+                    trace_simulation(&plans[0], simulation_data, generation_data),
+                ));
+            }
+            draw_queue_heatmaps(configuration_data, optimization_data, &traces)
+                .unwrap_or_else(|error| exit_with_error(&error));
+        }
+    }
+    let output_record = OutputRecord {
+        configuration_data,
+        generation_data,
+        optimization_data,
+        simulation_data,
+        runs,
     };
+    write_output(&output_record).unwrap_or_else(|error| exit_with_error(&error));
+}
 
-    let generation_data = GenerationData {
-        intersections: args.intersections,
-        timesteps: args.timesteps,
-        main_max_count: args.main_max_count,
-        side_max_count: args.side_max_count,
-        main_min_count: calculate_min_count(args.main_max_count),
-        side_min_count: calculate_min_count(args.side_max_count),
-    };
+/// Fails before optimizing if the baseline plan for the heatmaps can not be loaded.
+fn check_baseline(configuration_data: &ConfigurationData, generation_data: &GenerationData) {
+    if let Some(baseline) = &configuration_data.baseline {
+        load_plans(baseline, generation_data).unwrap_or_else(|error| exit_with_error(&error));
+    }
+}
 
-    let traffic_data;
-    if configuration_data.data == "generate" {
-        traffic_data = generate_data(&generation_data);
-        println!("{:?}", traffic_data);
-    } else {
-        traffic_data = fixed_data();
+fn run_generate(args: GenerateArgs) {
+    if let Some(seed) = args.generation.seed {
+        seed_generator(seed);
     }
+    let generation_data = build_generation_data(&args.generation);
+    println!("{:?}", generate_data(&generation_data));
+}
 
-    let optimization_data = OptimizationData {
-        iterations: args.iterations,
-        optimization: args.optimization,
-        mutation: args.mutation,
-        recombination: args.recombination,
-        probability_bitflip: args.probability_bitflip,
-        probability_recombination: args.probability_recombination,
-        population_size: args.population_size,
-        parents_size: args.parents_size,
-        tournament_size: args.tournament_size,
-        fitness_value: args.fitness_value,
-    };
+fn run_optimize(args: OptimizeArgs) {
+    if let Some(path) = args.resume {
+        let checkpoint = load_checkpoint(&path).unwrap_or_else(|error| exit_with_error(&error));
+        let mut plot_data = PlotData::default();
+        let result = resume(&checkpoint, &mut plot_data);
+        finish_runs(
+            &checkpoint.configuration_data,
+            &checkpoint.generation_data,
+            &checkpoint.optimization_data,
+            &checkpoint.simulation_data,
+            &vec![build_run_record(&result, &plot_data)],
+            &plot_data,
+        );
+        return;
+    }
+
+    let mut configuration_data =
+        build_configuration_data(args.generation.seed, &args.simulation.data);
+    apply_report_args(&mut configuration_data, &args.report)
+        .unwrap_or_else(|error| exit_with_error(&error));
+    configuration_data.checkpoint = args.checkpoint;
+    configuration_data.checkpoint_interval = args.checkpoint_interval;
+
+    let (generation_data, simulation_data) =
+        build_scenario(&configuration_data, &args.generation, &args.simulation)
+            .unwrap_or_else(|error| exit_with_error(&error));
+    let optimization_data = build_optimization_data(&args.optimizer, &generation_data)
+        .unwrap_or_else(|error| exit_with_error(&error));
+    check_baseline(&configuration_data, &generation_data);
+
+    let mut plot_data = PlotData::default();
+    let result = optimize(
+        &configuration_data,
+        &optimization_data,
+        &simulation_data,
+        &generation_data,
+        &mut plot_data,
+    );
+    finish_runs(
+        &configuration_data,
+        &generation_data,
+        &optimization_data,
+        &simulation_data,
+        &vec![build_run_record(&result, &plot_data)],
+        &plot_data,
+    );
+}
+
+fn run_evaluate(args: EvaluateArgs) {
+    let configuration_data = build_configuration_data(args.generation.seed, &args.simulation.data);
+    let (generation_data, simulation_data) =
+        build_scenario(&configuration_data, &args.generation, &args.simulation)
+            .unwrap_or_else(|error| exit_with_error(&error));
+    let plans =
+        load_plans(&args.plan, &generation_data).unwrap_or_else(|error| exit_with_error(&error));
+    evaluate(
+        &plans,
+        &simulation_data,
+        &generation_data,
+        if args.print_simulation {
+            Some(args.simulation_view.as_str())
+        } else {
+            None
+        },
+    );
+}
+
+fn run_benchmark(args: BenchmarkArgs) {
+    let mut configuration_data =
+        build_configuration_data(args.generation.seed, &args.simulation.data);
+    apply_report_args(&mut configuration_data, &args.report)
+        .unwrap_or_else(|error| exit_with_error(&error));
+    configuration_data.benchmark = true;
+    configuration_data.benchmark_iterations = args.runs;
+    configuration_data.runs_file = args.runs_file;
+
+    let (generation_data, simulation_data) =
+        build_scenario(&configuration_data, &args.generation, &args.simulation)
+            .unwrap_or_else(|error| exit_with_error(&error));
+    let optimization_data = build_optimization_data(&args.optimizer, &generation_data)
+        .unwrap_or_else(|error| exit_with_error(&error));
+    check_baseline(&configuration_data, &generation_data);
+
+    let mut plot_data = PlotData::default();
+    let runs = benchmark(
+        &configuration_data,
+        &optimization_data,
+        &simulation_data,
+        &generation_data,
+        &mut plot_data,
+    )
+    .unwrap_or_else(|error| exit_with_error(&error));
+    finish_runs(
+        &configuration_data,
+        &generation_data,
+        &optimization_data,
+        &simulation_data,
+        &runs,
+        &plot_data,
+    );
+}
 
-    let simulation_data = SimulationData {
-        traffic_data,
-        disable_increasing_passthrough: args.disable_increasing_passthrough,
-        disable_max_passthrough: args.disable_max_passthrough,
-        max_passthrough: calculate_max_passthrough(args.main_max_count),
-        main_percentage: args.main_percentage,
-        side_percentage: args.side_percentage,
+fn run_compare(args: CompareArgs) {
+    let mut configuration_data =
+        build_configuration_data(args.generation.seed, &args.simulation.data);
+    configuration_data.silent = true;
+    configuration_data.quiet = true;
+    configuration_data.plot = args.plot;
+    apply_plot_args(&mut configuration_data, &args.plot_options)
+        .unwrap_or_else(|error| exit_with_error(&error));
+
+    let (generation_data, simulation_data) =
+        build_scenario(&configuration_data, &args.generation, &args.simulation)
+            .unwrap_or_else(|error| exit_with_error(&error));
+    let optimization_data = build_optimization_data(&args.optimizer, &generation_data)
+        .unwrap_or_else(|error| exit_with_error(&error));
+    let mut variants = build_variants(&args.variants, &optimization_data)
+        .unwrap_or_else(|error| exit_with_error(&error));
+
+    let seed = match args.generation.seed {
+        Some(seed) => seed,
+        None => generator().gen::<u32>() as u64,
     };
+    compare(
+        &configuration_data,
+        &mut variants,
+        &simulation_data,
+        &generation_data,
+        args.runs,
+        seed,
+    )
+    .unwrap_or_else(|error| exit_with_error(&error));
+}
 
-    let mut plot_data: Vec<f64> = Vec::new();
-    if configuration_data.plot {
-        plot_data = Vec::with_capacity(optimization_data.iterations);
-    }
+fn run_tune(args: TuneArgs) {
+    let configuration_data = build_configuration_data(args.generation.seed, &args.simulation.data);
+
+    let (generation_data, simulation_data) =
+        build_scenario(&configuration_data, &args.generation, &args.simulation)
+            .unwrap_or_else(|error| exit_with_error(&error));
+    let optimization_data = build_optimization_data(&args.optimizer, &generation_data)
+        .unwrap_or_else(|error| exit_with_error(&error));
+    let tuning_data = build_tuning_data(&args, &optimization_data)
+        .unwrap_or_else(|error| exit_with_error(&error));
+    tune(
+        &configuration_data,
+        &optimization_data,
+        &simulation_data,
+        &generation_data,
+        &tuning_data,
+    );
+}
 
-    if configuration_data.benchmark {
-        let mut accumulated_results = 0.0;
-        let mut accumulated_durations = 0.0;
-        for _ in 0..configuration_data.benchmark_iterations {
-            let start = Instant::now();
-            accumulated_results += optimize(
-                &configuration_data,
-                &optimization_data,
-                &simulation_data,
-                &generation_data,
-                &mut plot_data,
-            );
-            accumulated_durations += start.elapsed().as_secs_f64();
-        }
-        println!(
-            "Mean of best individual over {} iterations: {:.4}",
-            configuration_data.benchmark_iterations,
-            accumulated_results / configuration_data.benchmark_iterations as f64
-        );
-        println!(
-            "Mean of optimization duration over {} iterations: {:.4}s",
-            configuration_data.benchmark_iterations,
-            accumulated_durations / configuration_data.benchmark_iterations as f64
-        );
+fn run_sweep(args: SweepArgs) {
+    let grid = parse_grid(&args.grid).unwrap_or_else(|error| exit_with_error(&error));
+    let scenarios = if args.scenarios.is_empty() {
+        vec![String::new()]
     } else {
-        optimize(
-            &configuration_data,
-            &optimization_data,
-            &simulation_data,
-            &generation_data,
-            &mut plot_data,
-        );
-    }
+        args.scenarios
+    };
+    let seed = match args.generation.seed {
+        Some(seed) => seed,
+        None => generator().gen::<u32>() as u64,
+    };
+    let sweep_data = SweepData {
+        grid,
+        scenarios,
+        repetitions: args.repetitions,
+        threads: args.threads,
+        seed,
+        results_file: args.results_file,
+    };
+    sweep(
+        &sweep_data,
+        &args.generation,
+        &args.simulation,
+        &args.optimizer,
+    )
+    .unwrap_or_else(|error| exit_with_error(&error));
+}
 
-    if configuration_data.plot {
-        let now: DateTime<Local> = Local::now();
-        let mut plot_path = String::from("plots/");
-        plot_path.push_str(&optimization_data.optimization);
-        plot_path.push_str("--");
-        if optimization_data.optimization == "genetic" {
-            plot_path.push_str(&optimization_data.recombination);
-            plot_path.push_str("--");
-            plot_path.push_str(&optimization_data.population_size.to_string());
-            plot_path.push_str("--");
-        }
-        plot_path.push_str(&optimization_data.iterations.to_string());
-        plot_path.push_str("--");
-        plot_path.push_str(&now.format("%F-%H-%M-%S").to_string());
-        plot_path.push_str(".png");
-
-        let plot_draw_area = BitMapBackend::new(&plot_path, (800, 600)).into_drawing_area();
-        plot_draw_area.fill(&WHITE).unwrap();
-
-        let (hightest_index, lowest_index) = get_highest_and_lowest(&plot_data);
-        let plot_min = (plot_data[lowest_index] * 10.0).floor() / 10.0;
-        let plot_max = (plot_data[hightest_index] * 10.0).ceil() / 10.0;
-
-        let mut ctx = ChartBuilder::on(&plot_draw_area)
-            .margin(30)
-            .set_label_area_size(LabelAreaPosition::Bottom, 20)
-            .set_label_area_size(LabelAreaPosition::Left, 20)
-            .build_cartesian_2d(0..optimization_data.iterations, plot_min..plot_max)
-            .unwrap();
-
-        ctx.configure_mesh()
-            .light_line_style(&WHITE)
-            .draw()
-            .unwrap();
-
-        ctx.draw_series(LineSeries::new(
-            (0..optimization_data.iterations).map(|x| (x, plot_data[x])),
-            &BLUE,
-        ))
-        .unwrap();
+fn configured<T: Serialize + DeserializeOwned>(args: T, source: &ConfigSource) -> T {
+    configure(args, source).unwrap_or_else(|error| exit_with_error(&error))
+}
+
+fn main() {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    let root = Cli::command();
+    let (command, command_matches) = match matches.subcommand() {
+        Some((name, command_matches)) => (root.find_subcommand(name).unwrap(), command_matches),
+        None => (&root, &matches),
+    };
+    let source = ConfigSource {
+        root: &root,
+        command,
+        matches: command_matches,
+        config: cli.config,
+        dump_config: cli.dump_config,
+    };
+
+    match cli.command {
+        Some(Command::Generate(args)) => run_generate(configured(args, &source)),
+        Some(Command::Optimize(args)) => run_optimize(configured(args, &source)),
+        Some(Command::Evaluate(args)) => run_evaluate(configured(args, &source)),
+        Some(Command::Benchmark(args)) => run_benchmark(configured(args, &source)),
+        Some(Command::Compare(args)) => run_compare(configured(args, &source)),
+        Some(Command::Tune(args)) => run_tune(configured(args, &source)),
+        Some(Command::Sweep(args)) => run_sweep(configured(args, &source)),
+        None => run_optimize(configured(cli.optimize, &source)),
     }
 }
@@ -0,0 +1,610 @@
+use crate::data::Link;
+use crate::data::Phase;
+use crate::data::TrafficState;
+use crate::phases::parse_phases;
+use serde::Deserialize;
+use std::fs;
+
+pub const APPROACHES: [&str; 3] = ["main_from_prev", "main_from_next", "side"];
+pub const DEFAULT_MAIN_PERCENTAGE: f64 = 0.8;
+pub const DEFAULT_SIDE_PERCENTAGE: f64 = 0.6;
+pub const DEFAULT_ROWS: usize = 1;
+/// Movements of the cars of an approach, main road cars drive through or turn off it and side
+/// road cars turn towards the next or the previous intersection or drive through.
+pub const TURNS: [&str; 7] = [
+    "main_from_prev_through",
+    "main_from_prev_turn",
+    "main_from_next_through",
+    "main_from_next_turn",
+    "side_to_next",
+    "side_to_prev",
+    "side_through",
+];
+/// Approach of every turn.
+pub const TURN_APPROACHES: [usize; 7] = [0, 0, 1, 1, 2, 2, 2];
+
+const MAIN_FROM_PREV: usize = 0;
+const MAIN_FROM_NEXT: usize = 1;
+const SIDE: usize = 2;
+
+/// Cars of an approach, the approaches are numbered like `APPROACHES`.
+pub fn get_cars(traffic: &TrafficState, approach: usize) -> i32 {
+    match approach {
+        MAIN_FROM_PREV => traffic.main_from_prev,
+        MAIN_FROM_NEXT => traffic.main_from_next,
+        _ => traffic.side,
+    }
+}
+
+pub fn get_cars_mut(traffic: &mut TrafficState, approach: usize) -> &mut i32 {
+    match approach {
+        MAIN_FROM_PREV => &mut traffic.main_from_prev,
+        MAIN_FROM_NEXT => &mut traffic.main_from_next,
+        _ => &mut traffic.side,
+    }
+}
+
+/// Turn of a link taking cars from `from_approach` to `to_approach`, main road cars staying in
+/// their direction drive through and side road cars turn into the direction they join.
+pub fn get_turn(from_approach: usize, to_approach: usize) -> usize {
+    match (from_approach, to_approach) {
+        (MAIN_FROM_PREV, MAIN_FROM_PREV) => 0,
+        (MAIN_FROM_PREV, _) => 1,
+        (MAIN_FROM_NEXT, MAIN_FROM_NEXT) => 2,
+        (MAIN_FROM_NEXT, _) => 3,
+        (_, MAIN_FROM_PREV) => 4,
+        (_, MAIN_FROM_NEXT) => 5,
+        _ => 6,
+    }
+}
+
+fn build_link(
+    from: usize,
+    from_approach: usize,
+    to: usize,
+    to_approach: usize,
+    ratio: f64,
+) -> Link {
+    Link {
+        from,
+        from_approach,
+        to,
+        to_approach,
+        turn: get_turn(from_approach, to_approach),
+        ratio,
+        capacity: None,
+        travel_time: 1,
+    }
+}
+
+/// Whether any link limits the cars entering it, so queues can spill back.
+pub fn has_link_capacities(links: &Vec<Link>) -> bool {
+    links.iter().any(|link| link.capacity.is_some())
+}
+
+/// Phases, saturation flows and turning ratios of one intersection of a street or grid.
+#[derive(Debug, Clone)]
+pub struct IntersectionSettings {
+    pub phases: Vec<Phase>,
+    pub max_passthrough: TrafficState,
+    pub main_from_prev_percentage: f64,
+    pub main_from_next_percentage: f64,
+    pub side_to_next_percentage: f64,
+    pub side_to_prev_percentage: f64,
+}
+
+/// Settings of every intersection taken from the global phases, max passthrough and percentages,
+/// side road cars turn into each direction of the main road with half of `side_percentage`.
+pub fn build_intersection_settings(
+    intersections: usize,
+    phases: &Vec<Phase>,
+    max_passthrough: i32,
+    main_percentage: f64,
+    side_percentage: f64,
+) -> Vec<IntersectionSettings> {
+    let settings = IntersectionSettings {
+        phases: phases.clone(),
+        max_passthrough: TrafficState {
+            main_from_prev: max_passthrough,
+            main_from_next: max_passthrough,
+            side: max_passthrough,
+        },
+        main_from_prev_percentage: main_percentage,
+        main_from_next_percentage: main_percentage,
+        side_to_next_percentage: side_percentage / 2.0,
+        side_to_prev_percentage: side_percentage / 2.0,
+    };
+    vec![settings; intersections]
+}
+
+/// Checks that max passthroughs are not negative and that the percentages are shares between 0
+/// and 1, with the side road cars turning into both directions together taking at most all.
+pub fn check_intersection_settings(settings: &Vec<IntersectionSettings>) -> Result<(), String> {
+    for (index, intersection) in settings.iter().enumerate() {
+        for (approach, name) in APPROACHES.iter().enumerate() {
+            if get_cars(&intersection.max_passthrough, approach) < 0 {
+                return Err(format!(
+                    "Max passthrough of {} at intersection {} must not be negative",
+                    name, index
+                ));
+            }
+        }
+        let percentages = [
+            intersection.main_from_prev_percentage,
+            intersection.main_from_next_percentage,
+            intersection.side_to_next_percentage,
+            intersection.side_to_prev_percentage,
+            intersection.side_to_next_percentage + intersection.side_to_prev_percentage,
+        ];
+        if percentages
+            .iter()
+            .any(|percentage| !(0.0..=1.0 + 1e-9).contains(percentage))
+        {
+            return Err(format!(
+                "Percentages of intersection {} must be between 0 and 1",
+                index
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Links of a grid of streets along the main road, the intersections are numbered row by row.
+/// Within a row cars stay on the main road with the main percentage of their direction and side
+/// road cars turn into each direction with its side percentage. The side roads are one-way cross
+/// streets connecting the rows, southwards in even and northwards in odd columns, which the main
+/// road cars not staying on it turn into and the other side road cars follow. A single row is a
+/// linear street, where cars leaving the main road leave the network.
+pub fn build_grid_links(
+    rows: usize,
+    settings: &Vec<IntersectionSettings>,
+) -> Result<Vec<Link>, String> {
+    let intersections = settings.len();
+    if rows < 1 || !intersections.is_multiple_of(rows) {
+        return Err(format!(
+            "{} intersections can not be laid out in {} rows",
+            intersections, rows
+        ));
+    }
+    let columns = intersections / rows;
+    let mut links: Vec<Link> = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            let index = row * columns + column;
+            let intersection = &settings[index];
+            if column + 1 < columns {
+                links.push(build_link(
+                    index,
+                    MAIN_FROM_PREV,
+                    index + 1,
+                    MAIN_FROM_PREV,
+                    intersection.main_from_prev_percentage,
+                ));
+                links.push(build_link(
+                    index,
+                    SIDE,
+                    index + 1,
+                    MAIN_FROM_PREV,
+                    intersection.side_to_next_percentage,
+                ));
+            }
+            if column > 0 {
+                links.push(build_link(
+                    index,
+                    MAIN_FROM_NEXT,
+                    index - 1,
+                    MAIN_FROM_NEXT,
+                    intersection.main_from_next_percentage,
+                ));
+                links.push(build_link(
+                    index,
+                    SIDE,
+                    index - 1,
+                    MAIN_FROM_NEXT,
+                    intersection.side_to_prev_percentage,
+                ));
+            }
+            let cross_row = if column % 2 == 0 {
+                Some(row + 1).filter(|row| *row < rows)
+            } else {
+                row.checked_sub(1)
+            };
+            if let Some(cross_row) = cross_row {
+                let cross_index = cross_row * columns + column;
+                links.push(build_link(
+                    index,
+                    MAIN_FROM_PREV,
+                    cross_index,
+                    SIDE,
+                    1.0 - intersection.main_from_prev_percentage,
+                ));
+                links.push(build_link(
+                    index,
+                    MAIN_FROM_NEXT,
+                    cross_index,
+                    SIDE,
+                    1.0 - intersection.main_from_next_percentage,
+                ));
+                links.push(build_link(
+                    index,
+                    SIDE,
+                    cross_index,
+                    SIDE,
+                    1.0 - (intersection.side_to_next_percentage
+                        + intersection.side_to_prev_percentage),
+                ));
+            }
+        }
+    }
+    Ok(links)
+}
+
+/// Shares of the cars of every approach taking each turn, numbered like `TURNS`, given by the
+/// percentages of the intersections.
+pub fn get_turn_shares(settings: &Vec<IntersectionSettings>) -> Vec<Vec<f64>> {
+    settings
+        .iter()
+        .map(|intersection| {
+            vec![
+                intersection.main_from_prev_percentage,
+                1.0 - intersection.main_from_prev_percentage,
+                intersection.main_from_next_percentage,
+                1.0 - intersection.main_from_next_percentage,
+                intersection.side_to_next_percentage,
+                intersection.side_to_prev_percentage,
+                1.0 - (intersection.side_to_next_percentage + intersection.side_to_prev_percentage),
+            ]
+        })
+        .collect()
+}
+
+/// Shares of the cars of every approach taking each turn given by the ratios of the links. Cars
+/// of an approach not taken by any link leave the network turning off the main road or driving
+/// through on the side road.
+pub fn get_link_turn_shares(intersections: usize, links: &Vec<Link>) -> Vec<Vec<f64>> {
+    let mut shares = vec![vec![0.0; TURNS.len()]; intersections];
+    for link in links.iter() {
+        shares[link.from][link.turn] += link.ratio;
+    }
+    for turns in shares.iter_mut() {
+        turns[1] = 1.0 - turns[0];
+        turns[3] = 1.0 - turns[2];
+        turns[6] = 1.0 - (turns[4] + turns[5]);
+    }
+    shares
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IntersectionEntry {
+    phases: Option<String>,
+    max_passthrough: Option<i32>,
+    main_max_passthrough: Option<i32>,
+    main_from_prev_max_passthrough: Option<i32>,
+    main_from_next_max_passthrough: Option<i32>,
+    side_max_passthrough: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LinkEntry {
+    from: usize,
+    from_approach: String,
+    to: usize,
+    to_approach: String,
+    turn: Option<String>,
+    ratio: f64,
+    capacity: Option<i32>,
+    travel_time: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NetworkEntry {
+    intersections: Vec<IntersectionEntry>,
+    #[serde(default)]
+    links: Vec<LinkEntry>,
+}
+
+/// Intersections and links of a road network file.
+pub struct Network {
+    pub phases: Vec<Vec<Phase>>,
+    pub max_passthroughs: Vec<TrafficState>,
+    pub links: Vec<Link>,
+}
+
+fn parse_approach(approach: &str, link: usize) -> Result<usize, String> {
+    APPROACHES
+        .iter()
+        .position(|name| *name == approach)
+        .ok_or(format!(
+            "Invalid approach {} of link {}, possible approaches: {}",
+            approach,
+            link,
+            APPROACHES.join(", ")
+        ))
+}
+
+fn parse_turn(turn: &str, from_approach: usize, link: usize) -> Result<usize, String> {
+    let turn = TURNS.iter().position(|name| *name == turn).ok_or(format!(
+        "Invalid turn {} of link {}, possible turns: {}",
+        turn,
+        link,
+        TURNS.join(", ")
+    ))?;
+    if TURN_APPROACHES[turn] != from_approach {
+        return Err(format!(
+            "Turn {} of link {} does not start at {}",
+            TURNS[turn], link, APPROACHES[from_approach]
+        ));
+    }
+    Ok(turn)
+}
+
+/// Loads a TOML network file with a list of intersections and a list of links between their
+/// approaches and checks that the links connect existing intersections, take a share between 0
+/// and 1 of the cars and do not take more than all cars of an approach together. Intersections
+/// without phases switch between `phases` and max passthroughs missing for an approach are
+/// `max_passthrough`, the more specific keys override the general. The turn of a link follows
+/// from its approaches unless it is given.
+pub fn load_network(
+    path: &str,
+    phases: &Vec<Phase>,
+    max_passthrough: i32,
+) -> Result<Network, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|error| format!("Could not read network from {}: {}", path, error))?;
+    let entry: NetworkEntry = toml::from_str(&content)
+        .map_err(|error| format!("Could not parse network {}: {}", path, error))?;
+
+    let intersections = entry.intersections.len();
+    if intersections == 0 {
+        return Err(format!("Network {} has no intersections", path));
+    }
+    let mut intersection_phases: Vec<Vec<Phase>> = Vec::with_capacity(intersections);
+    let mut max_passthroughs: Vec<TrafficState> = Vec::with_capacity(intersections);
+    for (index, intersection) in entry.intersections.iter().enumerate() {
+        intersection_phases.push(match &intersection.phases {
+            Some(text) => parse_phases(text)
+                .map_err(|error| format!("Phases of intersection {}: {}", index, error))?,
+            None => phases.clone(),
+        });
+        let max = intersection.max_passthrough.unwrap_or(max_passthrough);
+        let main_max = intersection.main_max_passthrough.unwrap_or(max);
+        let max_passthrough = TrafficState {
+            main_from_prev: intersection
+                .main_from_prev_max_passthrough
+                .unwrap_or(main_max),
+            main_from_next: intersection
+                .main_from_next_max_passthrough
+                .unwrap_or(main_max),
+            side: intersection.side_max_passthrough.unwrap_or(max),
+        };
+        max_passthroughs.push(max_passthrough);
+    }
+
+    let mut links: Vec<Link> = Vec::with_capacity(entry.links.len());
+    let mut shares = vec![[0.0; 3]; intersections];
+    for (index, link) in entry.links.iter().enumerate() {
+        if link.from >= intersections || link.to >= intersections {
+            return Err(format!(
+                "Link {} connects intersections {} and {} but the network has {}",
+                index, link.from, link.to, intersections
+            ));
+        }
+        if !(0.0..=1.0).contains(&link.ratio) {
+            return Err(format!(
+                "Ratio {} of link {} is not between 0 and 1",
+                link.ratio, index
+            ));
+        }
+        if link.capacity.is_some_and(|capacity| capacity < 1) {
+            return Err(format!("Capacity of link {} must be at least 1", index));
+        }
+        if link.travel_time == Some(0) {
+            return Err(format!("Travel time of link {} must be at least 1", index));
+        }
+        let from_approach = parse_approach(&link.from_approach, index)?;
+        let to_approach = parse_approach(&link.to_approach, index)?;
+        let turn = match &link.turn {
+            Some(turn) => parse_turn(turn, from_approach, index)?,
+            None => get_turn(from_approach, to_approach),
+        };
+        shares[link.from][from_approach] += link.ratio;
+        links.push(Link {
+            from: link.from,
+            from_approach,
+            to: link.to,
+            to_approach,
+            turn,
+            ratio: link.ratio,
+            capacity: link.capacity,
+            travel_time: link.travel_time.unwrap_or(1),
+        });
+    }
+    for (index, approaches) in shares.iter().enumerate() {
+        for (approach, share) in approaches.iter().enumerate() {
+            if *share > 1.0 + 1e-9 {
+                return Err(format!(
+                    "Links take {} of the cars of {} at intersection {}, more than all",
+                    share, APPROACHES[approach], index
+                ));
+            }
+        }
+    }
+    Ok(Network {
+        phases: intersection_phases,
+        max_passthroughs,
+        links,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_settings(intersections: usize) -> Vec<IntersectionSettings> {
+        build_intersection_settings(
+            intersections,
+            &parse_phases("side,main").unwrap(),
+            16,
+            0.8,
+            0.6,
+        )
+    }
+
+    fn find_ratio(links: &Vec<Link>, from: usize, from_approach: usize, to: usize) -> f64 {
+        links
+            .iter()
+            .find(|link| link.from == from && link.from_approach == from_approach && link.to == to)
+            .map(|link| link.ratio)
+            .unwrap()
+    }
+
+    #[test]
+    fn street_links_keep_cars_on_the_main_road() {
+        let links = build_grid_links(1, &build_settings(3)).unwrap();
+        assert_eq!(links.len(), 8);
+        assert!(links.iter().all(|link| link.to_approach != SIDE));
+        assert_eq!(find_ratio(&links, 0, MAIN_FROM_PREV, 1), 0.8);
+        assert_eq!(find_ratio(&links, 2, MAIN_FROM_NEXT, 1), 0.8);
+        assert_eq!(find_ratio(&links, 1, SIDE, 2), 0.3);
+        assert_eq!(find_ratio(&links, 1, SIDE, 0), 0.3);
+    }
+
+    #[test]
+    fn grid_links_turn_the_other_cars_into_the_cross_streets() {
+        let mut settings = build_settings(4);
+        settings[3].main_from_next_percentage = 0.9;
+        let links = build_grid_links(2, &settings).unwrap();
+        // The first column leads southwards from row 0 and the second northwards from row 1
+        let cross: Vec<&Link> = links
+            .iter()
+            .filter(|link| link.to_approach == SIDE)
+            .collect();
+        assert_eq!(cross.len(), 6);
+        assert!(cross
+            .iter()
+            .all(|link| (link.from, link.to) == (0, 2) || (link.from, link.to) == (3, 1)));
+        assert!((find_ratio(&links, 0, MAIN_FROM_PREV, 2) - 0.2).abs() < 1e-9);
+        assert!((find_ratio(&links, 0, SIDE, 2) - 0.4).abs() < 1e-9);
+        assert_eq!(find_ratio(&links, 3, MAIN_FROM_NEXT, 2), 0.9);
+        assert!((find_ratio(&links, 3, MAIN_FROM_NEXT, 1) - 0.1).abs() < 1e-9);
+        for index in 0..4 {
+            for approach in 0..APPROACHES.len() {
+                let share: f64 = links
+                    .iter()
+                    .filter(|link| link.from == index && link.from_approach == approach)
+                    .map(|link| link.ratio)
+                    .sum();
+                assert!(share <= 1.0 + 1e-9);
+            }
+        }
+    }
+
+    fn load_test_network(name: &str, content: &str) -> Result<Network, String> {
+        let path = std::env::temp_dir().join(format!("traffic-lights-network-{}.toml", name));
+        fs::write(&path, content).unwrap();
+        let network = load_network(
+            path.to_str().unwrap(),
+            &parse_phases("side,main").unwrap(),
+            16,
+        );
+        fs::remove_file(&path).unwrap();
+        network
+    }
+
+    const TWO_INTERSECTIONS: &str = "[[intersections]]\n[[intersections]]\n";
+
+    fn with_link(link: &str) -> String {
+        format!("{}[[links]]\nfrom = 0\nto = 1\n{}", TWO_INTERSECTIONS, link)
+    }
+
+    #[test]
+    fn networks_load_intersections_and_links() {
+        let content = "
+            [[intersections]]
+            max_passthrough = 10
+            main_max_passthrough = 12
+            side_max_passthrough = 4
+            phases = \"main,main_from_next_turn,side\"
+
+            [[intersections]]
+
+            [[links]]
+            from = 0
+            from_approach = \"side\"
+            to = 1
+            to_approach = \"main_from_prev\"
+            ratio = 0.3
+            capacity = 20
+            travel_time = 2
+        ";
+        let network = load_test_network("valid", content).unwrap();
+        assert_eq!(network.max_passthroughs[0].main_from_prev, 12);
+        assert_eq!(network.max_passthroughs[0].side, 4);
+        assert_eq!(network.max_passthroughs[1].side, 16);
+        assert_eq!(network.phases[0].len(), 3);
+        assert_eq!(network.phases[1].len(), 2);
+        let link = &network.links[0];
+        assert_eq!(
+            (link.from_approach, link.to_approach),
+            (SIDE, MAIN_FROM_PREV)
+        );
+        assert_eq!(TURNS[link.turn], "side_to_next");
+        assert_eq!((link.capacity, link.travel_time), (Some(20), 2));
+    }
+
+    #[test]
+    fn invalid_networks_are_rejected() {
+        let link = "from_approach = \"main_from_prev\"\nto_approach = \"main_from_prev\"\n";
+        let invalid = [
+            ("empty", String::from("intersections = []\n")),
+            ("unknown_key", String::from("[[intersections]]\nmax = 3\n")),
+            (
+                "phases",
+                String::from("[[intersections]]\nphases = \"main,left\"\n"),
+            ),
+            ("ratio", with_link(&format!("{}ratio = 1.5\n", link))),
+            (
+                "capacity",
+                with_link(&format!("{}ratio = 0.5\ncapacity = 0\n", link)),
+            ),
+            (
+                "travel_time",
+                with_link(&format!("{}ratio = 0.5\ntravel_time = 0\n", link)),
+            ),
+            (
+                "approach",
+                with_link("from_approach = \"north\"\nto_approach = \"side\"\nratio = 0.5\n"),
+            ),
+            (
+                "turn",
+                with_link(&format!("{}ratio = 0.5\nturn = \"side_through\"\n", link)),
+            ),
+            (
+                "intersection",
+                with_link(&format!("{}ratio = 0.5\n", link)).replace("to = 1", "to = 2"),
+            ),
+            (
+                "shares",
+                with_link(&format!("{}ratio = 0.6\n", link))
+                    + &format!("[[links]]\nfrom = 0\nto = 1\n{}ratio = 0.6\n", link),
+            ),
+        ];
+        for (name, content) in invalid.iter() {
+            assert!(
+                load_test_network(name, content).is_err(),
+                "{} network was loaded",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn grids_need_whole_rows() {
+        assert!(build_grid_links(2, &build_settings(3)).is_err());
+        assert!(build_grid_links(0, &build_settings(3)).is_err());
+        assert!(build_grid_links(3, &build_settings(3)).is_ok());
+    }
+}
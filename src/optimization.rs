@@ -1,21 +1,51 @@
+use crate::adaptive::build_bandit;
+use crate::adaptive::print_operator_usage;
+use crate::adaptive::select_operator;
+use crate::adaptive::update_operator;
+use crate::adaptive::OperatorBandit;
+use crate::adaptive::MUTATION_OPERATORS;
+use crate::adaptive::RECOMBINATION_OPERATORS;
+use crate::checkpoint::candidate_format;
+use crate::checkpoint::candidates_format;
+use crate::checkpoint::save_checkpoint;
+use crate::checkpoint::Checkpoint;
 use crate::data::generate_candidate;
 use crate::data::generate_population;
 use crate::data::ConfigurationData;
 use crate::data::GenerationData;
 use crate::data::OptimizationData;
+use crate::data::OptimizationResult;
+use crate::data::PlotData;
 use crate::data::SimulationData;
+use crate::diversity::candidate_bits;
+use crate::diversity::get_mean_bit_entropy;
+use crate::diversity::get_mean_pairwise_distance;
+use crate::diversity::get_shared_values;
+use crate::diversity::hamming_distance;
+use crate::phases::repair_plan;
+use crate::render::print_simulation;
+use crate::seeding::seed_population;
 use crate::simulation::simulate;
 use crate::simulation::simulate_population;
+use crate::termination::build_termination_state;
+use crate::termination::print_termination;
+use crate::termination::record_improvement;
+use crate::termination::should_terminate;
+use crate::termination::TerminationState;
 use crate::utils::distinct_random;
+use crate::utils::generator;
 use crate::utils::get_best_and_worst_candidate;
 use crate::utils::get_mean_value;
+use crate::utils::restore_generator_state;
 use crate::utils::tournament;
 use bit_vec::BitVec;
+use rand::seq::SliceRandom;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 fn bitflip(input: &Vec<BitVec>) -> Vec<BitVec> {
     let mut modified = input.clone();
-    let mut rng = rand::thread_rng();
+    let mut rng = generator();
     let index1 = rng.gen_range(0..modified.len());
     let index2 = rng.gen_range(0..modified[index1].len());
     match modified[index1].get(index2) {
@@ -27,7 +57,7 @@ fn bitflip(input: &Vec<BitVec>) -> Vec<BitVec> {
 
 fn probability_bitflip(input: &Vec<BitVec>, probability: f64) -> Vec<BitVec> {
     let mut modified = input.clone();
-    let mut rng = rand::thread_rng();
+    let mut rng = generator();
     // let mut bits_modified = 0;
 
     for index1 in 0..modified.len() {
@@ -47,15 +77,21 @@ fn probability_bitflip(input: &Vec<BitVec>, probability: f64) -> Vec<BitVec> {
     modified
 }
 
-fn mutation(candidate: &Vec<BitVec>, optimization_data: &OptimizationData) -> Vec<BitVec> {
-    let mutated_candidate;
-    if optimization_data.mutation == "prob_bitflip" {
+fn mutation(
+    candidate: &Vec<BitVec>,
+    variant: &str,
+    optimization_data: &OptimizationData,
+    generation_data: &GenerationData,
+) -> Vec<BitVec> {
+    let mut mutated_candidate;
+    if variant == "prob_bitflip" {
         mutated_candidate = probability_bitflip(candidate, optimization_data.probability_bitflip);
-    } else if optimization_data.mutation == "bitflip" {
+    } else if variant == "bitflip" {
         mutated_candidate = bitflip(candidate);
     } else {
         mutated_candidate = candidate.clone();
     }
+    repair_plan(&mut mutated_candidate, generation_data);
     mutated_candidate
 }
 
@@ -64,8 +100,9 @@ fn one_point_crossover(
     input2: &Vec<BitVec>,
     generation_data: &GenerationData,
 ) -> (Vec<BitVec>, Vec<BitVec>) {
-    let mut rng = rand::thread_rng();
-    let random_index = rng.gen_range(1..generation_data.timesteps);
+    let mut rng = generator();
+    // Split between timesteps to keep the phase codes intact
+    let random_index = rng.gen_range(1..generation_data.timesteps) * generation_data.phase_bits;
 
     let mut crossover1: Vec<BitVec> = Vec::with_capacity(generation_data.intersections);
     let mut crossover2: Vec<BitVec> = Vec::with_capacity(generation_data.intersections);
@@ -127,15 +164,16 @@ fn two_point_crossover(
 fn recombination(
     candidate1: &Vec<BitVec>,
     candidate2: &Vec<BitVec>,
+    variant: &str,
     optimization_data: &OptimizationData,
     generation_data: &GenerationData,
 ) -> (Vec<BitVec>, Vec<BitVec>) {
-    let mut rng = rand::thread_rng();
+    let mut rng = generator();
 
     if rng.gen::<f64>() < optimization_data.probability_recombination {
-        if optimization_data.recombination == "one_point" {
+        if variant == "one_point" {
             return one_point_crossover(candidate1, candidate2, generation_data);
-        } else if optimization_data.recombination == "two_point" {
+        } else if variant == "two_point" {
             return two_point_crossover(candidate1, candidate2, generation_data);
         }
     }
@@ -145,6 +183,8 @@ fn recombination(
 fn selection(
     population: &Vec<Vec<BitVec>>,
     population_values: &Vec<f64>,
+    mutation_variant: &str,
+    recombination_variant: &str,
     optimization_data: &OptimizationData,
     generation_data: &GenerationData,
 ) -> Vec<Vec<BitVec>> {
@@ -156,41 +196,296 @@ fn selection(
         let (recomb1, recomb2) = recombination(
             &population[selected[randoms[0]]],
             &population[selected[randoms[1]]],
+            recombination_variant,
             optimization_data,
             generation_data,
         );
 
-        next_population.push(mutation(&recomb1, optimization_data));
-        next_population.push(mutation(&recomb2, optimization_data));
+        next_population.push(mutation(
+            &recomb1,
+            mutation_variant,
+            optimization_data,
+            generation_data,
+        ));
+        next_population.push(mutation(
+            &recomb2,
+            mutation_variant,
+            optimization_data,
+            generation_data,
+        ));
     }
     next_population
 }
 
-fn hillclimb(
-    configuration_data: &ConfigurationData,
+fn crowding_offspring(
+    population: &Vec<Vec<BitVec>>,
+    mutation_variant: &str,
+    recombination_variant: &str,
+    optimization_data: &OptimizationData,
+    generation_data: &GenerationData,
+) -> (Vec<Vec<BitVec>>, Vec<usize>) {
+    let mut rng = generator();
+    let mut parents: Vec<usize> = (0..population.len()).collect();
+    parents.shuffle(&mut rng);
+    parents.truncate(population.len() - population.len() % 2);
+
+    let mut offspring: Vec<Vec<BitVec>> = Vec::with_capacity(parents.len());
+    for pair in parents.chunks(2) {
+        let (recomb1, recomb2) = recombination(
+            &population[pair[0]],
+            &population[pair[1]],
+            recombination_variant,
+            optimization_data,
+            generation_data,
+        );
+
+        offspring.push(mutation(
+            &recomb1,
+            mutation_variant,
+            optimization_data,
+            generation_data,
+        ));
+        offspring.push(mutation(
+            &recomb2,
+            mutation_variant,
+            optimization_data,
+            generation_data,
+        ));
+    }
+    (offspring, parents)
+}
+
+/// Deterministic crowding, every child competes against its more similar parent and replaces it
+/// if it is at least as good.
+fn crowding_replacement(
+    population: &mut Vec<Vec<BitVec>>,
+    population_values: &mut Vec<f64>,
+    offspring: &Vec<Vec<BitVec>>,
+    offspring_values: &Vec<f64>,
+    parents: &Vec<usize>,
+) {
+    for (index, pair) in parents.chunks(2).enumerate() {
+        let children = &offspring[index * 2..index * 2 + 2];
+        let children_values = &offspring_values[index * 2..index * 2 + 2];
+        let straight = hamming_distance(&population[pair[0]], &children[0])
+            + hamming_distance(&population[pair[1]], &children[1]);
+        let crossed = hamming_distance(&population[pair[0]], &children[1])
+            + hamming_distance(&population[pair[1]], &children[0]);
+
+        let matches = if straight <= crossed {
+            [(pair[0], 0), (pair[1], 1)]
+        } else {
+            [(pair[0], 1), (pair[1], 0)]
+        };
+        for (parent, child) in matches {
+            if children_values[child] >= population_values[parent] {
+                population[parent] = children[child].clone();
+                population_values[parent] = children_values[child];
+            }
+        }
+    }
+}
+
+fn operator_variant(variant: &str, bandit: &mut OperatorBandit) -> String {
+    if variant == "adaptive" {
+        return select_operator(bandit);
+    }
+    variant.to_string()
+}
+
+fn offspring_improvement(previous_values: &Vec<f64>, values: &Vec<f64>) -> f64 {
+    let previous_mean = get_mean_value(previous_values);
+    let improved = values
+        .iter()
+        .filter(|value| **value > previous_mean)
+        .count();
+    improved as f64 / values.len() as f64
+}
+
+fn print_progress(
+    iteration: usize,
+    best: &Vec<BitVec>,
+    best_value: f64,
+    population: &Vec<Vec<BitVec>>,
+    population_values: &Vec<f64>,
+) {
+    println!(
+        "{}:\t{:?}\t{:.4}\t{:.4}\t{:.2}\t{:.4}",
+        iteration,
+        best,
+        best_value,
+        get_mean_value(population_values),
+        get_mean_pairwise_distance(population),
+        get_mean_bit_entropy(population)
+    );
+}
+
+fn best_seed(
     optimization_data: &OptimizationData,
     simulation_data: &SimulationData,
     generation_data: &GenerationData,
-    plot_data: &mut Vec<f64>,
-) -> f64 {
-    let mut candidate =
-        generate_candidate(generation_data.intersections, generation_data.timesteps);
-    let mut candidate_value = simulate(
-        &candidate,
+) -> Option<(Vec<BitVec>, f64)> {
+    if optimization_data.seed_fraction <= 0.0 || optimization_data.seed_candidates.is_empty() {
+        return None;
+    }
+    let seed_values = simulate_population(
+        &optimization_data.seed_candidates,
         simulation_data,
         optimization_data,
         generation_data,
-        false,
     );
-    if !configuration_data.silent {
-        println!("0:\t{:?}\t{}", candidate, candidate_value);
+    let (seed, seed_value, _) =
+        get_best_and_worst_candidate(&optimization_data.seed_candidates, &seed_values);
+    Some((seed, seed_value))
+}
+
+fn print_seed_improvement(best_seed_value: Option<f64>, best_value: f64) {
+    if let Some(seed_value) = best_seed_value {
+        println!(
+            "Improvement over best seeded candidate ({:.4}): {:.4}",
+            seed_value,
+            best_value - seed_value
+        );
     }
-    if configuration_data.plot {
-        plot_data.push(candidate_value)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizerState {
+    #[serde(with = "candidates_format")]
+    pub population: Vec<Vec<BitVec>>,
+    pub population_values: Vec<f64>,
+    #[serde(with = "candidate_format")]
+    pub best: Vec<BitVec>,
+    pub best_value: f64,
+    pub best_seed_value: Option<f64>,
+    pub termination_state: TerminationState,
+    pub mutation_bandit: OperatorBandit,
+    pub recombination_bandit: OperatorBandit,
+}
+
+fn records_iterations(configuration_data: &ConfigurationData) -> bool {
+    configuration_data.plot || configuration_data.output.is_some()
+}
+
+/// Records the values of the current iteration. The hillclimber has no population, so its mean
+/// and worst value are the value of its current candidate.
+fn push_plot_data(plot_data: &mut PlotData, state: &OptimizerState) {
+    plot_data.best_values.push(state.best_value);
+    if state.population.is_empty() {
+        plot_data.mean_values.push(state.best_value);
+        plot_data.worst_values.push(state.best_value);
+    } else {
+        let (_, _, worst_value) =
+            get_best_and_worst_candidate(&state.population, &state.population_values);
+        plot_data
+            .mean_values
+            .push(get_mean_value(&state.population_values));
+        plot_data.worst_values.push(worst_value);
+        plot_data.diversity_values.push(
+            get_mean_pairwise_distance(&state.population)
+                / candidate_bits(&state.population[0]) as f64,
+        );
+        plot_data
+            .entropy_values
+            .push(get_mean_bit_entropy(&state.population));
+    }
+    plot_data
+        .evaluations
+        .push(state.termination_state.evaluations);
+    plot_data
+        .durations
+        .push(state.termination_state.start.elapsed().as_secs_f64());
+}
+
+fn checkpoint_due(configuration_data: &ConfigurationData, state: &OptimizerState) -> bool {
+    configuration_data.checkpoint.is_some()
+        && configuration_data.checkpoint_interval > 0
+        && state
+            .termination_state
+            .iteration
+            .is_multiple_of(configuration_data.checkpoint_interval)
+}
+
+fn initial_hillclimb_state(
+    optimization_data: &OptimizationData,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+) -> OptimizerState {
+    let mut termination_state = build_termination_state();
+    let seed = best_seed(optimization_data, simulation_data, generation_data);
+    let best_seed_value = seed.as_ref().map(|(_, seed_value)| *seed_value);
+    let (candidate, candidate_value) = match seed {
+        Some(seed) => {
+            termination_state.evaluations += optimization_data.seed_candidates.len();
+            seed
+        }
+        None => {
+            let mut candidate = generate_candidate(
+                generation_data.intersections,
+                generation_data.timesteps * generation_data.phase_bits,
+            );
+            repair_plan(&mut candidate, generation_data);
+            let candidate_value = simulate(
+                &candidate,
+                simulation_data,
+                optimization_data,
+                generation_data,
+                false,
+            );
+            termination_state.evaluations += 1;
+            (candidate, candidate_value)
+        }
+    };
+
+    OptimizerState {
+        population: Vec::new(),
+        population_values: Vec::new(),
+        best: candidate,
+        best_value: candidate_value,
+        best_seed_value,
+        termination_state,
+        mutation_bandit: build_bandit(&MUTATION_OPERATORS, optimization_data),
+        recombination_bandit: build_bandit(&RECOMBINATION_OPERATORS, optimization_data),
     }
+}
 
-    for it in 0..optimization_data.iterations {
-        let mutated_candidate = mutation(&candidate, optimization_data);
+fn hillclimb(
+    configuration_data: &ConfigurationData,
+    optimization_data: &OptimizationData,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    plot_data: &mut PlotData,
+    resume_state: Option<OptimizerState>,
+) -> OptimizationResult {
+    let mut state = match resume_state {
+        Some(state) => state,
+        None => {
+            let state =
+                initial_hillclimb_state(optimization_data, simulation_data, generation_data);
+            if !configuration_data.silent {
+                println!("0:\t{:?}\t{}", state.best, state.best_value);
+            }
+            if records_iterations(configuration_data) {
+                push_plot_data(plot_data, &state);
+            }
+            state
+        }
+    };
+
+    while !should_terminate(
+        &mut state.termination_state,
+        state.best_value,
+        1,
+        optimization_data,
+    ) {
+        let mutation_variant =
+            operator_variant(&optimization_data.mutation, &mut state.mutation_bandit);
+        let mutated_candidate = mutation(
+            &state.best,
+            &mutation_variant,
+            optimization_data,
+            generation_data,
+        );
 
         let mutated_candidate_value = simulate(
             &mutated_candidate,
@@ -199,72 +494,186 @@ fn hillclimb(
             generation_data,
             false,
         );
-        if candidate_value < mutated_candidate_value {
-            candidate = mutated_candidate;
-            candidate_value = mutated_candidate_value;
+        state.termination_state.iteration += 1;
+        state.termination_state.evaluations += 1;
+        if optimization_data.mutation == "adaptive" {
+            let reward = if mutated_candidate_value > state.best_value {
+                1.0
+            } else {
+                0.0
+            };
+            update_operator(&mut state.mutation_bandit, reward);
+        }
+        if state.best_value < mutated_candidate_value {
+            state.best = mutated_candidate;
+            state.best_value = mutated_candidate_value;
+            record_improvement(&mut state.termination_state);
 
             if !configuration_data.silent {
-                println!("{}:\t{:?}\t{}", it + 1, candidate, candidate_value);
+                println!(
+                    "{}:\t{:?}\t{}",
+                    state.termination_state.iteration, state.best, state.best_value
+                );
             }
         }
-        if configuration_data.plot {
-            plot_data.push(candidate_value)
+        if records_iterations(configuration_data) {
+            push_plot_data(plot_data, &state);
+        }
+        if checkpoint_due(configuration_data, &state) {
+            save_checkpoint(
+                configuration_data,
+                optimization_data,
+                simulation_data,
+                generation_data,
+                &state,
+                plot_data,
+            );
         }
     }
+    if configuration_data.checkpoint.is_some() {
+        save_checkpoint(
+            configuration_data,
+            optimization_data,
+            simulation_data,
+            generation_data,
+            &state,
+            plot_data,
+        );
+    }
 
-    println!("Final candidate:");
-    println!("{:?}\t{:.4}", candidate, candidate_value);
+    if !configuration_data.quiet {
+        println!("Final candidate:");
+        println!("{:?}\t{:.4}", state.best, state.best_value);
+        print_seed_improvement(state.best_seed_value, state.best_value);
+        print_termination(&state.termination_state);
+        if optimization_data.mutation == "adaptive" {
+            print_operator_usage(&state.mutation_bandit, "Mutation");
+        }
+    }
     if configuration_data.print_final_simulation {
-        simulate(
-            &candidate,
+        print_simulation(
+            &state.best,
             simulation_data,
-            optimization_data,
             generation_data,
-            true,
+            &configuration_data.simulation_view,
         );
     }
-    candidate_value
+    OptimizationResult {
+        best_candidate: state.best,
+        best_value: state.best_value,
+        iterations: state.termination_state.iteration,
+        evaluations: state.termination_state.evaluations,
+        duration: state.termination_state.start.elapsed().as_secs_f64(),
+    }
 }
 
-fn genetic_algorithm(
-    configuration_data: &ConfigurationData,
+fn initial_genetic_state(
     optimization_data: &OptimizationData,
     simulation_data: &SimulationData,
     generation_data: &GenerationData,
-    plot_data: &mut Vec<f64>,
-) -> f64 {
+) -> OptimizerState {
+    let mut termination_state = build_termination_state();
     let mut population = generate_population(
         optimization_data.population_size,
         generation_data.intersections,
-        generation_data.timesteps,
+        generation_data.timesteps * generation_data.phase_bits,
     );
-    let mut population_values = simulate_population(
+    for candidate in population.iter_mut() {
+        repair_plan(candidate, generation_data);
+    }
+    let seeded = seed_population(&mut population, optimization_data);
+    let population_values = simulate_population(
         &population,
         simulation_data,
         optimization_data,
         generation_data,
     );
-    let (mut best, mut best_value, _) =
-        get_best_and_worst_candidate(&population, &population_values);
-    if !configuration_data.silent {
-        println!(
-            "0:\t{:?}\t{:.4}\t{:.4}",
-            best,
-            best_value,
-            get_mean_value(&population_values)
-        );
-    }
-    if configuration_data.plot {
-        plot_data.push(best_value)
+    termination_state.evaluations += population.len();
+    let best_seed_value = population_values[..seeded].iter().cloned().reduce(f64::max);
+    let (best, best_value, _) = get_best_and_worst_candidate(&population, &population_values);
+
+    OptimizerState {
+        population,
+        population_values,
+        best,
+        best_value,
+        best_seed_value,
+        termination_state,
+        mutation_bandit: build_bandit(&MUTATION_OPERATORS, optimization_data),
+        recombination_bandit: build_bandit(&RECOMBINATION_OPERATORS, optimization_data),
     }
+}
 
-    for it in 0..optimization_data.iterations {
-        let next_population = selection(
-            &population,
-            &population_values,
-            optimization_data,
-            generation_data,
+fn genetic_algorithm(
+    configuration_data: &ConfigurationData,
+    optimization_data: &OptimizationData,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    plot_data: &mut PlotData,
+    resume_state: Option<OptimizerState>,
+) -> OptimizationResult {
+    let mut state = match resume_state {
+        Some(state) => state,
+        None => {
+            let state = initial_genetic_state(optimization_data, simulation_data, generation_data);
+            if !configuration_data.silent {
+                print_progress(
+                    0,
+                    &state.best,
+                    state.best_value,
+                    &state.population,
+                    &state.population_values,
+                );
+            }
+            if records_iterations(configuration_data) {
+                push_plot_data(plot_data, &state);
+            }
+            state
+        }
+    };
+
+    let offspring_size = optimization_data.population_size - optimization_data.population_size % 2;
+    while !should_terminate(
+        &mut state.termination_state,
+        state.best_value,
+        offspring_size,
+        optimization_data,
+    ) {
+        let mutation_variant =
+            operator_variant(&optimization_data.mutation, &mut state.mutation_bandit);
+        let recombination_variant = operator_variant(
+            &optimization_data.recombination,
+            &mut state.recombination_bandit,
         );
+        let mut parents: Vec<usize> = Vec::new();
+        let next_population;
+        if optimization_data.replacement == "crowding" {
+            (next_population, parents) = crowding_offspring(
+                &state.population,
+                &mutation_variant,
+                &recombination_variant,
+                optimization_data,
+                generation_data,
+            );
+        } else {
+            let selection_values = if optimization_data.fitness_sharing {
+                get_shared_values(
+                    &state.population,
+                    &state.population_values,
+                    optimization_data,
+                )
+            } else {
+                state.population_values.clone()
+            };
+            next_population = selection(
+                &state.population,
+                &selection_values,
+                &mutation_variant,
+                &recombination_variant,
+                optimization_data,
+                generation_data,
+            );
+        }
 
         let next_population_values = simulate_population(
             &next_population,
@@ -272,57 +681,116 @@ fn genetic_algorithm(
             optimization_data,
             generation_data,
         );
+        state.termination_state.iteration += 1;
+        state.termination_state.evaluations += next_population.len();
 
-        population = next_population;
-        population_values = next_population_values;
+        let reward = offspring_improvement(&state.population_values, &next_population_values);
+        if optimization_data.mutation == "adaptive" {
+            update_operator(&mut state.mutation_bandit, reward);
+        }
+        if optimization_data.recombination == "adaptive" {
+            update_operator(&mut state.recombination_bandit, reward);
+        }
+
+        if optimization_data.replacement == "crowding" {
+            crowding_replacement(
+                &mut state.population,
+                &mut state.population_values,
+                &next_population,
+                &next_population_values,
+                &parents,
+            );
+        } else {
+            state.population = next_population;
+            state.population_values = next_population_values;
+        }
 
         let (next_best, next_best_value, _) =
-            get_best_and_worst_candidate(&population, &population_values);
-        if next_best_value > best_value {
-            best = next_best;
-            best_value = next_best_value;
+            get_best_and_worst_candidate(&state.population, &state.population_values);
+        if next_best_value > state.best_value {
+            state.best = next_best;
+            state.best_value = next_best_value;
+            record_improvement(&mut state.termination_state);
 
             if !configuration_data.silent {
-                println!(
-                    "{}:\t{:?}\t{:.4}\t{:.4}",
-                    it + 1,
-                    best,
-                    best_value,
-                    get_mean_value(&population_values)
+                print_progress(
+                    state.termination_state.iteration,
+                    &state.best,
+                    state.best_value,
+                    &state.population,
+                    &state.population_values,
                 );
             }
         }
-        if configuration_data.plot {
-            plot_data.push(best_value)
+        if records_iterations(configuration_data) {
+            push_plot_data(plot_data, &state);
+        }
+        if checkpoint_due(configuration_data, &state) {
+            save_checkpoint(
+                configuration_data,
+                optimization_data,
+                simulation_data,
+                generation_data,
+                &state,
+                plot_data,
+            );
         }
     }
+    if configuration_data.checkpoint.is_some() {
+        save_checkpoint(
+            configuration_data,
+            optimization_data,
+            simulation_data,
+            generation_data,
+            &state,
+            plot_data,
+        );
+    }
 
-    println!("Final candidate:");
-    println!(
-        "{:?}\t{:.4}\t{:.4}",
-        best,
-        best_value,
-        get_mean_value(&population_values)
-    );
+    if !configuration_data.quiet {
+        println!("Final candidate:");
+        println!(
+            "{:?}\t{:.4}\t{:.4}\t{:.2}\t{:.4}",
+            state.best,
+            state.best_value,
+            get_mean_value(&state.population_values),
+            get_mean_pairwise_distance(&state.population),
+            get_mean_bit_entropy(&state.population)
+        );
+        print_seed_improvement(state.best_seed_value, state.best_value);
+        print_termination(&state.termination_state);
+        if optimization_data.mutation == "adaptive" {
+            print_operator_usage(&state.mutation_bandit, "Mutation");
+        }
+        if optimization_data.recombination == "adaptive" {
+            print_operator_usage(&state.recombination_bandit, "Recombination");
+        }
+    }
     if configuration_data.print_final_simulation {
-        simulate(
-            &best,
+        print_simulation(
+            &state.best,
             simulation_data,
-            optimization_data,
             generation_data,
-            true,
+            &configuration_data.simulation_view,
         );
     }
-    best_value
+    OptimizationResult {
+        best_candidate: state.best,
+        best_value: state.best_value,
+        iterations: state.termination_state.iteration,
+        evaluations: state.termination_state.evaluations,
+        duration: state.termination_state.start.elapsed().as_secs_f64(),
+    }
 }
 
-pub fn optimize(
+fn run_optimization(
     configuration_data: &ConfigurationData,
     optimization_data: &OptimizationData,
     simulation_data: &SimulationData,
     generation_data: &GenerationData,
-    plot_data: &mut Vec<f64>,
-) -> f64 {
+    plot_data: &mut PlotData,
+    resume_state: Option<OptimizerState>,
+) -> OptimizationResult {
     if optimization_data.optimization == "genetic" {
         return genetic_algorithm(
             configuration_data,
@@ -330,15 +798,46 @@ pub fn optimize(
             simulation_data,
             generation_data,
             plot_data,
-        );
-    } else if optimization_data.optimization == "hillclimb" {
-        return hillclimb(
-            configuration_data,
-            optimization_data,
-            simulation_data,
-            generation_data,
-            plot_data,
+            resume_state,
         );
     }
-    0.0
+    hillclimb(
+        configuration_data,
+        optimization_data,
+        simulation_data,
+        generation_data,
+        plot_data,
+        resume_state,
+    )
+}
+
+pub fn optimize(
+    configuration_data: &ConfigurationData,
+    optimization_data: &OptimizationData,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    plot_data: &mut PlotData,
+) -> OptimizationResult {
+    run_optimization(
+        configuration_data,
+        optimization_data,
+        simulation_data,
+        generation_data,
+        plot_data,
+        None,
+    )
+}
+
+/// Continues an optimization from a checkpoint with the configuration stored in it.
+pub fn resume(checkpoint: &Checkpoint, plot_data: &mut PlotData) -> OptimizationResult {
+    restore_generator_state(&checkpoint.generator_state);
+    *plot_data = checkpoint.plot_data.clone();
+    run_optimization(
+        &checkpoint.configuration_data,
+        &checkpoint.optimization_data,
+        &checkpoint.simulation_data,
+        &checkpoint.generation_data,
+        plot_data,
+        Some(checkpoint.state.clone()),
+    )
 }
@@ -0,0 +1,147 @@
+use crate::checkpoint::candidate_format;
+use crate::data::format_candidate;
+use crate::data::ConfigurationData;
+use crate::data::GenerationData;
+use crate::data::OptimizationData;
+use crate::data::OptimizationResult;
+use crate::data::PlotData;
+use crate::data::SimulationData;
+use bit_vec::BitVec;
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Write};
+
+#[derive(Debug, Serialize)]
+pub struct IterationRecord {
+    pub iteration: usize,
+    pub best_value: f64,
+    pub mean_value: f64,
+    pub worst_value: f64,
+    pub evaluations: usize,
+    pub duration: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunRecord {
+    #[serde(with = "candidate_format")]
+    pub best_candidate: Vec<BitVec>,
+    pub best_value: f64,
+    pub iterations: usize,
+    pub evaluations: usize,
+    pub duration: f64,
+    pub history: Vec<IterationRecord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OutputRecord<'a> {
+    pub configuration_data: &'a ConfigurationData,
+    pub generation_data: &'a GenerationData,
+    pub optimization_data: &'a OptimizationData,
+    pub simulation_data: &'a SimulationData,
+    pub runs: &'a Vec<RunRecord>,
+}
+
+pub fn build_run_record(result: &OptimizationResult, plot_data: &PlotData) -> RunRecord {
+    let mut history: Vec<IterationRecord> = Vec::with_capacity(plot_data.best_values.len());
+    for index in 0..plot_data.best_values.len() {
+        history.push(IterationRecord {
+            iteration: index,
+            best_value: plot_data.best_values[index],
+            mean_value: plot_data.mean_values[index],
+            worst_value: plot_data.worst_values[index],
+            evaluations: plot_data.evaluations[index],
+            duration: plot_data.durations[index],
+        });
+    }
+
+    RunRecord {
+        best_candidate: result.best_candidate.clone(),
+        best_value: result.best_value,
+        iterations: result.iterations,
+        evaluations: result.evaluations,
+        duration: result.duration,
+        history,
+    }
+}
+
+fn format_json(record: &OutputRecord) -> Result<String, String> {
+    serde_json::to_string_pretty(record)
+        .map(|content| content + "\n")
+        .map_err(|error| format!("Could not serialize results: {}", error))
+}
+
+/// One row per iteration and run, the configuration and the best candidate of every run are
+/// written as comment lines starting with `#` in front of the table.
+fn format_csv(record: &OutputRecord) -> Result<String, String> {
+    let mut content = String::new();
+    for (name, value) in [
+        (
+            "configuration_data",
+            serde_json::to_string(record.configuration_data),
+        ),
+        (
+            "generation_data",
+            serde_json::to_string(record.generation_data),
+        ),
+        (
+            "optimization_data",
+            serde_json::to_string(record.optimization_data),
+        ),
+        (
+            "simulation_data",
+            serde_json::to_string(record.simulation_data),
+        ),
+    ] {
+        let value = value.map_err(|error| format!("Could not serialize results: {}", error))?;
+        content.push_str(&format!("# {}: {}\n", name, value));
+    }
+    for (run, run_record) in record.runs.iter().enumerate() {
+        content.push_str(&format!(
+            "# run {}: best_candidate={} best_value={} iterations={} evaluations={} duration={}\n",
+            run,
+            format_candidate(&run_record.best_candidate),
+            run_record.best_value,
+            run_record.iterations,
+            run_record.evaluations,
+            run_record.duration
+        ));
+    }
+
+    content.push_str("run,iteration,best_value,mean_value,worst_value,evaluations,duration\n");
+    for (run, run_record) in record.runs.iter().enumerate() {
+        for iteration in run_record.history.iter() {
+            content.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                run,
+                iteration.iteration,
+                iteration.best_value,
+                iteration.mean_value,
+                iteration.worst_value,
+                iteration.evaluations,
+                iteration.duration
+            ));
+        }
+    }
+    Ok(content)
+}
+
+/// Writes the results in the configured format to the output file or to stdout.
+pub fn write_output(record: &OutputRecord) -> Result<(), String> {
+    let format = match &record.configuration_data.output {
+        Some(format) => format,
+        None => return Ok(()),
+    };
+    let content = if format == "csv" {
+        format_csv(record)?
+    } else {
+        format_json(record)?
+    };
+
+    match &record.configuration_data.output_file {
+        Some(path) => fs::write(path, content)
+            .map_err(|error| format!("Could not write results to {}: {}", path, error)),
+        None => io::stdout()
+            .write_all(content.as_bytes())
+            .map_err(|error| format!("Could not write results: {}", error)),
+    }
+}
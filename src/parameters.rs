@@ -0,0 +1,309 @@
+use crate::arrivals::{ARRIVALS, ROBUST_FITNESS};
+use crate::cli::{GenerationArgs, SimulationArgs};
+use crate::data::OptimizationData;
+use crate::network::IntersectionSettings;
+use crate::phases::parse_phases;
+use crate::simulation::FITNESS_VALUES;
+use std::str::FromStr;
+
+pub const OPTIMIZATIONS: [&str; 2] = ["genetic", "hillclimb"];
+pub const MUTATIONS: [&str; 4] = ["none", "bitflip", "prob_bitflip", "adaptive"];
+pub const RECOMBINATIONS: [&str; 3] = ["one_point", "two_point", "adaptive"];
+pub const ADAPTIVE_STRATEGIES: [&str; 2] = ["ucb", "probability_matching"];
+pub const REPLACEMENTS: [&str; 2] = ["generational", "crowding"];
+pub const DATA_VARIANTS: [&str; 2] = ["fixed", "generate"];
+pub const SCENARIO_PARAMETERS: [&str; 23] = [
+    "intersections",
+    "timesteps",
+    "main_max_count",
+    "side_max_count",
+    "data",
+    "disable_increasing_passthrough",
+    "disable_max_passthrough",
+    "main_percentage",
+    "side_percentage",
+    "timestep_length",
+    "yellow_time",
+    "all_red_time",
+    "startup_lost_time",
+    "link_capacity",
+    "rows",
+    "network",
+    "phases",
+    "intersection",
+    "arrivals",
+    "samples",
+    "dispersion",
+    "robust_fitness",
+    "cvar_alpha",
+];
+pub const INTERSECTION_PARAMETERS: [&str; 12] = [
+    "phases",
+    "max_passthrough",
+    "main_max_passthrough",
+    "main_from_prev_max_passthrough",
+    "main_from_next_max_passthrough",
+    "side_max_passthrough",
+    "main_percentage",
+    "main_from_prev_percentage",
+    "main_from_next_percentage",
+    "side_percentage",
+    "side_to_next_percentage",
+    "side_to_prev_percentage",
+];
+
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .map_err(|_| format!("Invalid value {} for {}", value, name))
+}
+
+fn parse_choice(name: &str, value: &str, choices: &[&str]) -> Result<String, String> {
+    if !choices.contains(&value) {
+        return Err(format!(
+            "Invalid value {} for {}, possible values: {}",
+            value,
+            name,
+            choices.join(", ")
+        ));
+    }
+    Ok(value.to_string())
+}
+
+/// Sets an optimizer parameter by the name of its command line option with underscores.
+pub fn set_optimization_parameter(
+    optimization_data: &mut OptimizationData,
+    name: &str,
+    value: &str,
+) -> Result<(), String> {
+    match name {
+        "iterations" => optimization_data.iterations = parse_value(name, value)?,
+        "stagnation_iterations" => {
+            optimization_data.stagnation_iterations = Some(parse_value(name, value)?)
+        }
+        "target_value" => optimization_data.target_value = Some(parse_value(name, value)?),
+        "time_limit" => optimization_data.time_limit = Some(parse_value(name, value)?),
+        "max_evaluations" => optimization_data.max_evaluations = Some(parse_value(name, value)?),
+        "optimization" => {
+            optimization_data.optimization = parse_choice(name, value, &OPTIMIZATIONS)?
+        }
+        "mutation" => optimization_data.mutation = parse_choice(name, value, &MUTATIONS)?,
+        "recombination" => {
+            optimization_data.recombination = parse_choice(name, value, &RECOMBINATIONS)?
+        }
+        "adaptive_strategy" => {
+            optimization_data.adaptive_strategy = parse_choice(name, value, &ADAPTIVE_STRATEGIES)?
+        }
+        "probability_bitflip" => optimization_data.probability_bitflip = parse_value(name, value)?,
+        "probability_recombination" => {
+            optimization_data.probability_recombination = parse_value(name, value)?
+        }
+        "population_size" => optimization_data.population_size = parse_value(name, value)?,
+        "parents_size" => optimization_data.parents_size = parse_value(name, value)?,
+        "tournament_size" => optimization_data.tournament_size = parse_value(name, value)?,
+        "replacement" => optimization_data.replacement = parse_choice(name, value, &REPLACEMENTS)?,
+        "fitness_sharing" => optimization_data.fitness_sharing = parse_value(name, value)?,
+        "sharing_radius" => {
+            let radius: f64 = parse_value(name, value)?;
+            if radius <= 0.0 {
+                return Err(String::from("Sharing radius must be positive"));
+            }
+            optimization_data.sharing_radius = radius;
+        }
+        "seed_fraction" => {
+            let fraction: f64 = parse_value(name, value)?;
+            if !(0.0..=1.0).contains(&fraction) {
+                return Err(String::from("Seed fraction must be between 0 and 1"));
+            }
+            optimization_data.seed_fraction = fraction;
+        }
+        "fitness_value" => {
+            optimization_data.fitness_value = parse_choice(name, value, &FITNESS_VALUES)?
+        }
+        _ => return Err(format!("Unknown optimizer parameter {}", name)),
+    }
+    Ok(())
+}
+
+/// Sets a scenario parameter by the name of its command line option with underscores, every
+/// `intersection` adds the settings of one intersection like `--intersection`.
+pub fn set_scenario_parameter(
+    generation_args: &mut GenerationArgs,
+    simulation_args: &mut SimulationArgs,
+    name: &str,
+    value: &str,
+) -> Result<(), String> {
+    match name {
+        "intersections" => generation_args.intersections = parse_value(name, value)?,
+        "timesteps" => generation_args.timesteps = parse_value(name, value)?,
+        "main_max_count" => generation_args.main_max_count = parse_value(name, value)?,
+        "side_max_count" => generation_args.side_max_count = parse_value(name, value)?,
+        "data" => simulation_args.data = parse_choice(name, value, &DATA_VARIANTS)?,
+        "disable_increasing_passthrough" => {
+            simulation_args.disable_increasing_passthrough = parse_value(name, value)?
+        }
+        "disable_max_passthrough" => {
+            simulation_args.disable_max_passthrough = parse_value(name, value)?
+        }
+        "main_percentage" => simulation_args.main_percentage = parse_value(name, value)?,
+        "side_percentage" => simulation_args.side_percentage = parse_value(name, value)?,
+        "timestep_length" => simulation_args.timestep_length = parse_value(name, value)?,
+        "yellow_time" => simulation_args.yellow_time = parse_value(name, value)?,
+        "all_red_time" => simulation_args.all_red_time = parse_value(name, value)?,
+        "startup_lost_time" => simulation_args.startup_lost_time = parse_value(name, value)?,
+        "link_capacity" => simulation_args.link_capacity = Some(parse_value(name, value)?),
+        "rows" => simulation_args.rows = parse_value(name, value)?,
+        "network" => simulation_args.network = Some(value.to_string()),
+        "phases" => simulation_args.phases = value.to_string(),
+        "intersection" => simulation_args
+            .intersection_settings
+            .push(value.to_string()),
+        "arrivals" => simulation_args.arrivals = parse_choice(name, value, &ARRIVALS)?,
+        "samples" => simulation_args.samples = parse_value(name, value)?,
+        "dispersion" => simulation_args.dispersion = parse_value(name, value)?,
+        "robust_fitness" => {
+            simulation_args.robust_fitness = parse_choice(name, value, &ROBUST_FITNESS)?
+        }
+        "cvar_alpha" => simulation_args.cvar_alpha = parse_value(name, value)?,
+        _ => return Err(format!("Unknown scenario parameter {}", name)),
+    }
+    Ok(())
+}
+
+/// Checks that the genetic algorithm can select parents, tournament selection needs enough
+/// candidates left to fill every tournament without picking previous winners and recombination
+/// needs at least two parents.
+pub fn check_optimization_data(optimization_data: &OptimizationData) -> Result<(), String> {
+    if optimization_data.optimization == "genetic"
+        && (optimization_data.parents_size < 2
+            || optimization_data.parents_size + optimization_data.tournament_size
+                > optimization_data.population_size)
+    {
+        return Err(format!(
+            "The genetic algorithm needs at least 2 parents and a population size of at least \
+             the parents size plus the tournament size, got population size {}, parents size {} \
+             and tournament size {}",
+            optimization_data.population_size,
+            optimization_data.parents_size,
+            optimization_data.tournament_size
+        ));
+    }
+    Ok(())
+}
+
+/// Splits comma separated `name=value` assignments.
+pub fn parse_assignments(assignments: &str) -> Result<Vec<(String, String)>, String> {
+    let mut parsed: Vec<(String, String)> = Vec::new();
+    for assignment in assignments
+        .split(',')
+        .filter(|assignment| !assignment.trim().is_empty())
+    {
+        match assignment.split_once('=') {
+            Some((name, value)) => parsed.push((name.trim().to_string(), value.trim().to_string())),
+            None => return Err(format!("Expected name=value but got {}", assignment)),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Applies comma separated `name=value` assignments, e.g. `mutation=bitflip,population_size=20`.
+pub fn apply_assignments(
+    optimization_data: &mut OptimizationData,
+    assignments: &str,
+) -> Result<(), String> {
+    for (name, value) in parse_assignments(assignments)?.iter() {
+        set_optimization_parameter(optimization_data, name, value)?;
+    }
+    Ok(())
+}
+
+/// Sets the phases, a max passthrough or a turning percentage of one intersection, the names
+/// without a direction set both directions, `side_percentage` is split evenly between them.
+/// Phases are separated by slashes as commas separate the assignments.
+pub fn set_intersection_parameter(
+    settings: &mut IntersectionSettings,
+    name: &str,
+    value: &str,
+) -> Result<(), String> {
+    let max_passthrough = &mut settings.max_passthrough;
+    match name {
+        "phases" => settings.phases = parse_phases(value)?,
+        "max_passthrough" => {
+            let max: i32 = parse_value(name, value)?;
+            max_passthrough.main_from_prev = max;
+            max_passthrough.main_from_next = max;
+            max_passthrough.side = max;
+        }
+        "main_max_passthrough" => {
+            let max: i32 = parse_value(name, value)?;
+            max_passthrough.main_from_prev = max;
+            max_passthrough.main_from_next = max;
+        }
+        "main_from_prev_max_passthrough" => {
+            max_passthrough.main_from_prev = parse_value(name, value)?
+        }
+        "main_from_next_max_passthrough" => {
+            max_passthrough.main_from_next = parse_value(name, value)?
+        }
+        "side_max_passthrough" => max_passthrough.side = parse_value(name, value)?,
+        "main_percentage" => {
+            let percentage: f64 = parse_value(name, value)?;
+            settings.main_from_prev_percentage = percentage;
+            settings.main_from_next_percentage = percentage;
+        }
+        "main_from_prev_percentage" => {
+            settings.main_from_prev_percentage = parse_value(name, value)?
+        }
+        "main_from_next_percentage" => {
+            settings.main_from_next_percentage = parse_value(name, value)?
+        }
+        "side_percentage" => {
+            let percentage: f64 = parse_value(name, value)?;
+            settings.side_to_next_percentage = percentage / 2.0;
+            settings.side_to_prev_percentage = percentage / 2.0;
+        }
+        "side_to_next_percentage" => settings.side_to_next_percentage = parse_value(name, value)?,
+        "side_to_prev_percentage" => settings.side_to_prev_percentage = parse_value(name, value)?,
+        _ => {
+            return Err(format!(
+                "Unknown intersection parameter {}, possible parameters: {}",
+                name,
+                INTERSECTION_PARAMETERS.join(", ")
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Applies per intersection settings given as `index:name=value,...`, e.g.
+/// `2:side_max_passthrough=8,phases=main/main_from_next_turn/side`. Turning percentages are only
+/// allowed when the links are built from them.
+pub fn apply_intersection_settings(
+    settings: &mut Vec<IntersectionSettings>,
+    overrides: &Vec<String>,
+    turning_percentages: bool,
+) -> Result<(), String> {
+    for entry in overrides.iter() {
+        let (index, assignments) = entry
+            .split_once(':')
+            .ok_or(format!("Expected index:name=value,... but got {}", entry))?;
+        let index: usize = parse_value("intersection index", index.trim())?;
+        if index >= settings.len() {
+            return Err(format!(
+                "Intersection {} does not exist, there are {}",
+                index,
+                settings.len()
+            ));
+        }
+        for (name, value) in parse_assignments(assignments)?.iter() {
+            if !turning_percentages && name.ends_with("_percentage") {
+                return Err(format!(
+                    "{} of intersection {} is given by the links of the network file",
+                    name, index
+                ));
+            }
+            set_intersection_parameter(&mut settings[index], name, value)?;
+        }
+    }
+    Ok(())
+}
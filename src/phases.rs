@@ -0,0 +1,324 @@
+use crate::data::GenerationData;
+use crate::data::Phase;
+use crate::network::{APPROACHES, TURNS, TURN_APPROACHES};
+use crate::utils::generator;
+use bit_vec::BitVec;
+use rand::Rng;
+
+pub const DEFAULT_PHASES: &str = "side,main";
+pub const MOVEMENTS: [&str; 11] = [
+    "main",
+    "main_from_prev",
+    "main_from_next",
+    "side",
+    "main_from_prev_through",
+    "main_from_prev_turn",
+    "main_from_next_through",
+    "main_from_next_turn",
+    "side_to_next",
+    "side_to_prev",
+    "side_through",
+];
+
+/// Turns of an approach, numbered like `TURNS`.
+pub fn get_approach_turns(approach: usize) -> Vec<usize> {
+    (0..TURNS.len())
+        .filter(|turn| TURN_APPROACHES[*turn] == approach)
+        .collect()
+}
+
+/// Whether the phase gives green to every turn of the approach.
+pub fn serves_approach(phase: &Phase, approach: usize) -> bool {
+    get_approach_turns(approach)
+        .iter()
+        .all(|turn| phase.turns[*turn])
+}
+
+/// Whether the phase gives green to the whole main road and nothing else.
+pub fn is_main_phase(phase: &Phase) -> bool {
+    serves_approach(phase, 0)
+        && serves_approach(phase, 1)
+        && !get_approach_turns(2).iter().any(|turn| phase.turns[*turn])
+}
+
+/// Whether the phase gives green to the whole side road and nothing else.
+pub fn is_side_phase(phase: &Phase) -> bool {
+    serves_approach(phase, 2)
+        && !get_approach_turns(0)
+            .iter()
+            .chain(get_approach_turns(1).iter())
+            .any(|turn| phase.turns[*turn])
+}
+
+fn movement_turns(movement: &str) -> Option<Vec<usize>> {
+    if movement == "main" {
+        let mut turns = get_approach_turns(0);
+        turns.extend(get_approach_turns(1));
+        return Some(turns);
+    }
+    if let Some(approach) = APPROACHES.iter().position(|name| *name == movement) {
+        return Some(get_approach_turns(approach));
+    }
+    TURNS
+        .iter()
+        .position(|name| *name == movement)
+        .map(|turn| vec![turn])
+}
+
+/// Parses phases separated by commas or slashes, each a list of the movements it serves joined
+/// by `+`, e.g. `side,main` or `main,main_from_next_turn,side`. `main` serves both directions of
+/// the main road, an approach serves all of its turns and a turn like `main_from_next_turn` only
+/// the cars of the approach taking it. Slashes allow phases in values already separated by
+/// commas, like sweep grids and intersection settings.
+pub fn parse_phases(text: &str) -> Result<Vec<Phase>, String> {
+    let mut phases: Vec<Phase> = Vec::new();
+    for part in text.split([',', '/']).map(|part| part.trim()) {
+        let mut phase = Phase {
+            turns: vec![false; TURNS.len()],
+        };
+        for movement in part.split('+').map(|movement| movement.trim()) {
+            match movement_turns(movement) {
+                Some(turns) => {
+                    for turn in turns {
+                        phase.turns[turn] = true;
+                    }
+                }
+                None => {
+                    return Err(format!(
+                        "Invalid movement '{}' in phase '{}', possible movements: {}",
+                        movement,
+                        part,
+                        MOVEMENTS.join(", ")
+                    ))
+                }
+            }
+        }
+        phases.push(phase);
+    }
+    if phases.len() < 2 {
+        return Err(String::from("Expected at least two phases"));
+    }
+    Ok(phases)
+}
+
+/// Movements of a phase, approaches with all turns by the approach and the whole main road as
+/// `main`.
+pub fn format_phase(phase: &Phase) -> String {
+    let mut movements: Vec<&str> = Vec::new();
+    if serves_approach(phase, 0) && serves_approach(phase, 1) {
+        movements.push("main");
+    }
+    for (approach, name) in APPROACHES.iter().enumerate() {
+        if approach < 2 && movements.contains(&"main") {
+            continue;
+        }
+        if serves_approach(phase, approach) {
+            movements.push(name);
+            continue;
+        }
+        for turn in get_approach_turns(approach) {
+            if phase.turns[turn] {
+                movements.push(TURNS[turn]);
+            }
+        }
+    }
+    movements.join("+")
+}
+
+/// Number of bits encoding the phase of one timestep.
+pub fn get_phase_bits(phases: usize) -> usize {
+    let mut bits = 1;
+    while (1 << bits) < phases {
+        bits += 1;
+    }
+    bits
+}
+
+fn read_code(lights: &BitVec, t: usize, bits: usize) -> usize {
+    let mut code = 0;
+    for bit in t * bits..(t + 1) * bits {
+        code = (code << 1) | lights[bit] as usize;
+    }
+    code
+}
+
+fn write_code(lights: &mut BitVec, t: usize, bits: usize, code: usize) {
+    for (offset, bit) in (t * bits..(t + 1) * bits).enumerate() {
+        lights.set(bit, (code >> (bits - 1 - offset)) & 1 == 1);
+    }
+}
+
+/// Index of the phase intersection `index` shows in timestep `t`. Plans only hold codes of
+/// existing phases, `repair_plan` replaces the others, so wrapping around never picks a phase.
+pub fn decode_phase(
+    lights: &BitVec,
+    index: usize,
+    t: usize,
+    generation_data: &GenerationData,
+) -> usize {
+    read_code(lights, t, generation_data.phase_bits) % generation_data.phases[index].len()
+}
+
+/// Phase indices of every intersection and timestep of a candidate.
+pub fn decode_plan(candidate: &Vec<BitVec>, generation_data: &GenerationData) -> Vec<Vec<usize>> {
+    candidate
+        .iter()
+        .enumerate()
+        .map(|(index, lights)| {
+            (0..generation_data.timesteps)
+                .map(|t| decode_phase(lights, index, t, generation_data))
+                .collect()
+        })
+        .collect()
+}
+
+/// Replaces codes beyond the phases of an intersection, which exist when its number of phases is
+/// not a power of two, with a uniformly drawn phase, so no phase is more likely than the others
+/// after generating or mutating a plan.
+pub fn repair_plan(candidate: &mut Vec<BitVec>, generation_data: &GenerationData) {
+    let bits = generation_data.phase_bits;
+    let mut rng = generator();
+    for (index, lights) in candidate.iter_mut().enumerate() {
+        let phases = generation_data.phases[index].len();
+        for t in 0..generation_data.timesteps {
+            if read_code(lights, t, bits) >= phases {
+                write_code(lights, t, bits, rng.gen_range(0..phases));
+            }
+        }
+    }
+}
+
+/// Checks that a given plan only holds codes of existing phases.
+pub fn check_plan(candidate: &Vec<BitVec>, generation_data: &GenerationData) -> Result<(), String> {
+    for (index, lights) in candidate.iter().enumerate() {
+        let phases = generation_data.phases[index].len();
+        for t in 0..generation_data.timesteps {
+            let code = read_code(lights, t, generation_data.phase_bits);
+            if code >= phases {
+                return Err(format!(
+                    "Phase {} of intersection {} in timestep {} does not exist, it has {} phases",
+                    code, index, t, phases
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Encodes a plan giving either the main road or the side road green into phases, using for
+/// every intersection its first phase serving all of the main road and its first phase serving
+/// the side road.
+pub fn encode_main_side_plan(plan: &Vec<BitVec>, generation_data: &GenerationData) -> Vec<BitVec> {
+    let bits = generation_data.phase_bits;
+    let mut candidate: Vec<BitVec> = Vec::with_capacity(plan.len());
+    for (index, lights) in plan.iter().enumerate() {
+        let phases = &generation_data.phases[index];
+        let main_phase = phases
+            .iter()
+            .position(|phase| serves_approach(phase, 0) && serves_approach(phase, 1))
+            .unwrap_or(0);
+        let side_phase = phases
+            .iter()
+            .position(|phase| serves_approach(phase, 2))
+            .unwrap_or(0);
+        let mut data = BitVec::from_elem(lights.len() * bits, false);
+        for (t, main_green) in lights.iter().enumerate() {
+            let code = if main_green { main_phase } else { side_phase };
+            write_code(&mut data, t, bits, code);
+        }
+        candidate.push(data);
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_generation_data(phases: Vec<Vec<Phase>>, timesteps: usize) -> GenerationData {
+        let phase_bits = phases
+            .iter()
+            .map(|phases| get_phase_bits(phases.len()))
+            .max()
+            .unwrap();
+        GenerationData {
+            intersections: phases.len(),
+            timesteps,
+            main_max_count: 20,
+            side_max_count: 10,
+            main_min_count: 7,
+            side_min_count: 3,
+            phases,
+            phase_bits,
+        }
+    }
+
+    #[test]
+    fn phases_parse_movements_and_turns() {
+        let phases = parse_phases("main, main_from_next_turn/side+main_from_prev").unwrap();
+        assert_eq!(phases.len(), 3);
+        assert!(is_main_phase(&phases[0]));
+        assert_eq!(
+            phases[1].turns,
+            vec![false, false, false, true, false, false, false]
+        );
+        assert!(serves_approach(&phases[2], 0));
+        assert!(serves_approach(&phases[2], 2));
+        assert!(!phases[2].turns[2]);
+        assert!(parse_phases("main").is_err());
+        assert!(parse_phases("main,left").is_err());
+    }
+
+    #[test]
+    fn formatted_phases_parse_to_the_same_phases() {
+        let text = "main,side,main_from_prev+side_to_next,main_from_next_turn+side_through";
+        let phases = parse_phases(text).unwrap();
+        let formatted: Vec<String> = phases.iter().map(format_phase).collect();
+        assert_eq!(formatted.join(","), text);
+        assert_eq!(parse_phases(&formatted.join("/")).unwrap(), phases);
+    }
+
+    #[test]
+    fn phase_bits_hold_all_phases() {
+        assert_eq!(get_phase_bits(2), 1);
+        assert_eq!(get_phase_bits(3), 2);
+        assert_eq!(get_phase_bits(4), 2);
+        assert_eq!(get_phase_bits(5), 3);
+    }
+
+    #[test]
+    fn main_side_plans_decode_to_the_main_and_side_phases() {
+        let generation_data = build_generation_data(
+            vec![
+                parse_phases("side,main").unwrap(),
+                parse_phases("main_from_next_turn,main,side").unwrap(),
+            ],
+            4,
+        );
+        let plan = vec![BitVec::from_fn(4, |t| t % 2 == 0); 2];
+        let candidate = encode_main_side_plan(&plan, &generation_data);
+        assert_eq!(candidate[0].len(), 8);
+        assert_eq!(
+            decode_plan(&candidate, &generation_data),
+            vec![vec![1, 0, 1, 0], vec![1, 2, 1, 2]]
+        );
+        assert!(check_plan(&candidate, &generation_data).is_ok());
+    }
+
+    #[test]
+    fn repaired_plans_only_show_existing_phases() {
+        let generation_data = build_generation_data(
+            vec![
+                parse_phases("main,main_from_next_turn,side").unwrap(),
+                parse_phases("side,main").unwrap(),
+            ],
+            3,
+        );
+        let mut candidate = vec![BitVec::from_elem(6, true), BitVec::from_elem(6, false)];
+        assert!(check_plan(&candidate, &generation_data).is_err());
+        repair_plan(&mut candidate, &generation_data);
+        assert!(check_plan(&candidate, &generation_data).is_ok());
+        // Codes of existing phases stay as they are
+        assert_eq!(candidate[1], BitVec::from_elem(6, false));
+    }
+}
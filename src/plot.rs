@@ -0,0 +1,695 @@
+use crate::data::ConfigurationData;
+use crate::data::GenerationData;
+use crate::data::Link;
+use crate::data::OptimizationData;
+use crate::data::Phase;
+use crate::data::PlotData;
+use crate::data::SimulationData;
+use crate::data::SimulationTrace;
+use crate::data::TrafficState;
+use crate::network::get_cars;
+use crate::phases::decode_plan;
+use crate::phases::format_phase;
+use crate::phases::{is_main_phase, is_side_phase};
+use crate::statistics::get_convergence_values;
+use crate::statistics::get_quantile;
+use crate::utils::get_highest_and_lowest;
+use bit_vec::BitVec;
+use chrono::{DateTime, Local};
+use plotters::coord::{CoordTranslate, Shift};
+use plotters::prelude::{
+    BitMapBackend, ChartBuilder, ChartContext, DrawingArea, DrawingBackend, IntoDrawingArea,
+    LabelAreaPosition, LineSeries, PathElement, Polygon, Rectangle, SVGBackend,
+    SeriesLabelPosition,
+};
+use plotters::style::{
+    Color, Palette, Palette99, RGBColor, BLACK, BLUE, GREEN, MAGENTA, RED, WHITE,
+};
+use std::fs;
+use std::path::Path;
+
+/// Path of a plot in the plot directory, which is created if missing. A configured plot file is
+/// used for the main plot and gets the kind of the plot appended for all others, otherwise the
+/// name is generated from the kind, the optimizer and the current time.
+fn build_plot_path(
+    configuration_data: &ConfigurationData,
+    kind: &str,
+    optimization_data: Option<&OptimizationData>,
+) -> Result<String, String> {
+    fs::create_dir_all(&configuration_data.plot_dir).map_err(|error| {
+        format!(
+            "Could not create plot directory {}: {}",
+            configuration_data.plot_dir, error
+        )
+    })?;
+
+    let mut plot_name = String::new();
+    match &configuration_data.plot_file {
+        Some(plot_file) => {
+            plot_name.push_str(plot_file);
+            if !kind.is_empty() {
+                plot_name.push('-');
+                plot_name.push_str(kind);
+            }
+        }
+        None => {
+            if !kind.is_empty() {
+                plot_name.push_str(kind);
+                plot_name.push_str("--");
+            }
+            if let Some(optimization_data) = optimization_data {
+                plot_name.push_str(&optimization_data.optimization);
+                plot_name.push_str("--");
+                if optimization_data.optimization == "genetic" {
+                    plot_name.push_str(&optimization_data.recombination);
+                    plot_name.push_str("--");
+                    plot_name.push_str(&optimization_data.population_size.to_string());
+                    plot_name.push_str("--");
+                }
+                plot_name.push_str(&optimization_data.iterations.to_string());
+                plot_name.push_str("--");
+            }
+            let now: DateTime<Local> = Local::now();
+            plot_name.push_str(&now.format("%F-%H-%M-%S").to_string());
+        }
+    }
+    plot_name.push('.');
+    plot_name.push_str(&configuration_data.plot_format);
+
+    Ok(Path::new(&configuration_data.plot_dir)
+        .join(plot_name)
+        .to_string_lossy()
+        .to_string())
+}
+
+/// Title describing the configuration the plot was made with.
+fn build_plot_title(
+    configuration_data: &ConfigurationData,
+    optimization_data: Option<&OptimizationData>,
+) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(optimization_data) = optimization_data {
+        parts.push(optimization_data.optimization.clone());
+        parts.push(format!("{} mutation", optimization_data.mutation));
+        if optimization_data.optimization == "genetic" {
+            parts.push(format!("{} recombination", optimization_data.recombination));
+            parts.push(format!("population {}", optimization_data.population_size));
+        }
+        parts.push(format!("{} iterations", optimization_data.iterations));
+        parts.push(format!("{} fitness", optimization_data.fitness_value));
+    }
+    parts.push(format!("{} data", configuration_data.data));
+    if let Some(seed) = configuration_data.seed {
+        parts.push(format!("seed {}", seed));
+    }
+    parts.join(", ")
+}
+
+/// Fills the plot white and returns the area below the title.
+fn prepare_area<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+) -> DrawingArea<DB, Shift> {
+    root.fill(&WHITE).unwrap();
+    root.titled(title, ("sans-serif", 16)).unwrap()
+}
+
+fn present_plot<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    plot_path: &str,
+) -> Result<(), String> {
+    root.present()
+        .map_err(|error| format!("Could not write plot {}: {}", plot_path, error))
+}
+
+/// Content of a plot with everything needed to draw it on any backend.
+enum Plot<'a> {
+    Run(&'a PlotData),
+    Benchmark(&'a Vec<Vec<f64>>, usize),
+    Comparison(&'a Vec<(String, Vec<f64>)>),
+    TimeSpace(TimeSpaceData<'a>),
+    Queues(&'a Vec<(String, SimulationTrace)>),
+}
+
+struct TimeSpaceData<'a> {
+    plan: Vec<Vec<usize>>,
+    phases: &'a Vec<Vec<Phase>>,
+    links: &'a Vec<Link>,
+    trace: &'a SimulationTrace,
+    platoons: bool,
+}
+
+fn draw_plot_values<DB: DrawingBackend>(draw_area: &DrawingArea<DB, Shift>, plot: &Plot) {
+    match plot {
+        Plot::Run(plot_data) => draw_run_values(draw_area, plot_data),
+        Plot::Benchmark(quantiles, runs) => draw_benchmark_values(draw_area, quantiles, *runs),
+        Plot::Comparison(curves) => draw_comparison_values(draw_area, curves),
+        Plot::TimeSpace(data) => draw_time_space_values(draw_area, data),
+        Plot::Queues(traces) => draw_queue_values(draw_area, traces),
+    }
+}
+
+fn draw_to_file<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    plot_path: &str,
+    title: &str,
+    plot: &Plot,
+) -> Result<(), String> {
+    draw_plot_values(&prepare_area(&root, title), plot);
+    present_plot(&root, plot_path)
+}
+
+/// Writes the plot as SVG or PNG depending on the configured plot format.
+fn write_plot(
+    configuration_data: &ConfigurationData,
+    plot_path: &str,
+    size: (u32, u32),
+    title: &str,
+    plot: &Plot,
+) -> Result<(), String> {
+    if configuration_data.plot_format == "svg" {
+        let root = SVGBackend::new(plot_path, size).into_drawing_area();
+        draw_to_file(root, plot_path, title, plot)
+    } else {
+        let root = BitMapBackend::new(plot_path, size).into_drawing_area();
+        draw_to_file(root, plot_path, title, plot)
+    }
+}
+
+/// Range of the values rounded outwards to one decimal place.
+fn get_plot_range(values: &Vec<f64>) -> (f64, f64) {
+    let (hightest_index, lowest_index) = get_highest_and_lowest(values);
+    let plot_min = (values[lowest_index] * 10.0).floor() / 10.0;
+    let mut plot_max = (values[hightest_index] * 10.0).ceil() / 10.0;
+    if plot_max <= plot_min {
+        plot_max = plot_min + 0.1;
+    }
+    (plot_min, plot_max)
+}
+
+fn draw_series_labels<'a, DB: DrawingBackend + 'a, CT: CoordTranslate>(
+    ctx: &mut ChartContext<'a, DB, CT>,
+    position: SeriesLabelPosition,
+) {
+    ctx.configure_series_labels()
+        .position(position)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .unwrap();
+}
+
+/// Draws the best value and, for populations, the mean and worst value of each iteration.
+fn draw_fitness_values<DB: DrawingBackend>(
+    draw_area: &DrawingArea<DB, Shift>,
+    plot_data: &PlotData,
+    iterations: usize,
+    with_population: bool,
+) {
+    let mut values = plot_data.best_values.clone();
+    if with_population {
+        values.extend(plot_data.worst_values.iter());
+    }
+    let (plot_min, plot_max) = get_plot_range(&values);
+
+    let mut ctx = ChartBuilder::on(draw_area)
+        .margin(30)
+        .set_label_area_size(LabelAreaPosition::Bottom, 40)
+        .set_label_area_size(LabelAreaPosition::Left, 50)
+        .build_cartesian_2d(0..iterations, plot_min..plot_max)
+        .unwrap();
+
+    ctx.configure_mesh()
+        .light_line_style(WHITE)
+        .x_desc("iteration")
+        .y_desc("fitness value")
+        .draw()
+        .unwrap();
+
+    let mut lines = vec![("best", &plot_data.best_values, BLUE)];
+    if with_population {
+        lines.push(("population mean", &plot_data.mean_values, GREEN));
+        lines.push(("worst", &plot_data.worst_values, RED));
+    }
+    for (name, values, color) in lines.into_iter() {
+        ctx.draw_series(LineSeries::new(
+            (0..iterations).map(|x| (x, values[x])),
+            &color,
+        ))
+        .unwrap()
+        .label(name)
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    draw_series_labels(&mut ctx, SeriesLabelPosition::LowerRight);
+}
+
+fn draw_diversity_values<DB: DrawingBackend>(
+    draw_area: &DrawingArea<DB, Shift>,
+    plot_data: &PlotData,
+    iterations: usize,
+) {
+    let mut ctx = ChartBuilder::on(draw_area)
+        .margin(30)
+        .set_label_area_size(LabelAreaPosition::Bottom, 40)
+        .set_label_area_size(LabelAreaPosition::Left, 50)
+        .build_cartesian_2d(0..iterations, 0.0..1.0)
+        .unwrap();
+
+    ctx.configure_mesh()
+        .light_line_style(WHITE)
+        .x_desc("iteration")
+        .y_desc("diversity")
+        .draw()
+        .unwrap();
+
+    ctx.draw_series(LineSeries::new(
+        (0..iterations).map(|x| (x, plot_data.diversity_values[x])),
+        &RED,
+    ))
+    .unwrap()
+    .label("mean pairwise distance")
+    .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+    ctx.draw_series(LineSeries::new(
+        (0..iterations).map(|x| (x, plot_data.entropy_values[x])),
+        &GREEN,
+    ))
+    .unwrap()
+    .label("mean bit entropy")
+    .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], GREEN));
+    draw_series_labels(&mut ctx, SeriesLabelPosition::UpperRight);
+}
+
+fn draw_run_values<DB: DrawingBackend>(draw_area: &DrawingArea<DB, Shift>, plot_data: &PlotData) {
+    let iterations = plot_data.best_values.len();
+    // Only populations have diversity values, the hillclimber has just its best value
+    if plot_data.diversity_values.is_empty() {
+        draw_fitness_values(draw_area, plot_data, iterations, false);
+    } else {
+        let areas = draw_area.split_evenly((2, 1));
+        draw_fitness_values(&areas[0], plot_data, iterations, true);
+        draw_diversity_values(&areas[1], plot_data, iterations);
+    }
+}
+
+/// Draws the best, mean and worst values and, if recorded, the normalized mean pairwise hamming
+/// distance and mean bit entropy of the population below.
+pub fn draw_plot(
+    configuration_data: &ConfigurationData,
+    plot_data: &PlotData,
+    optimization_data: &OptimizationData,
+) -> Result<(), String> {
+    if plot_data.best_values.is_empty() {
+        return Ok(());
+    }
+    let plot_path = build_plot_path(configuration_data, "", Some(optimization_data))?;
+    let title = build_plot_title(configuration_data, Some(optimization_data));
+    write_plot(
+        configuration_data,
+        &plot_path,
+        configuration_data.plot_size,
+        &title,
+        &Plot::Run(plot_data),
+    )
+}
+
+fn draw_benchmark_values<DB: DrawingBackend>(
+    draw_area: &DrawingArea<DB, Shift>,
+    quantiles: &Vec<Vec<f64>>,
+    runs: usize,
+) {
+    let iterations = quantiles[2].len();
+    let mut range_values = quantiles[0].clone();
+    range_values.extend(quantiles[4].iter());
+    let (plot_min, plot_max) = get_plot_range(&range_values);
+
+    let mut ctx = ChartBuilder::on(draw_area)
+        .margin(30)
+        .set_label_area_size(LabelAreaPosition::Bottom, 40)
+        .set_label_area_size(LabelAreaPosition::Left, 50)
+        .build_cartesian_2d(0..iterations, plot_min..plot_max)
+        .unwrap();
+
+    ctx.configure_mesh()
+        .light_line_style(WHITE)
+        .x_desc("iteration")
+        .y_desc(format!("best value over {} runs", runs))
+        .draw()
+        .unwrap();
+
+    let bands = [
+        ("min - max", &quantiles[0], &quantiles[4], 0.15),
+        ("interquartile range", &quantiles[1], &quantiles[3], 0.35),
+    ];
+    for (name, lower, upper, opacity) in bands.into_iter() {
+        let color = BLUE.mix(opacity);
+        let mut points: Vec<(usize, f64)> = (0..iterations).map(|x| (x, upper[x])).collect();
+        points.extend((0..iterations).rev().map(|x| (x, lower[x])));
+        ctx.draw_series(std::iter::once(Polygon::new(points, color.filled())))
+            .unwrap()
+            .label(name)
+            .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], color.filled()));
+    }
+    ctx.draw_series(LineSeries::new(
+        (0..iterations).map(|x| (x, quantiles[2][x])),
+        &BLUE,
+    ))
+    .unwrap()
+    .label("median")
+    .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+    draw_series_labels(&mut ctx, SeriesLabelPosition::LowerRight);
+}
+
+/// Draws the median best value per iteration over all benchmark runs with bands of the
+/// interquartile range and of the minimum and maximum.
+pub fn draw_benchmark_plot(
+    configuration_data: &ConfigurationData,
+    run_best_values: &Vec<Vec<f64>>,
+    optimization_data: &OptimizationData,
+) -> Result<(), String> {
+    let values = get_convergence_values(run_best_values);
+    if values.is_empty() {
+        return Ok(());
+    }
+    let quantiles: Vec<Vec<f64>> = [0.0, 0.25, 0.5, 0.75, 1.0]
+        .iter()
+        .map(|quantile| {
+            values
+                .iter()
+                .map(|values| get_quantile(values, *quantile))
+                .collect()
+        })
+        .collect();
+    let plot_path = build_plot_path(configuration_data, "benchmark", Some(optimization_data))?;
+    let title = build_plot_title(configuration_data, Some(optimization_data));
+    write_plot(
+        configuration_data,
+        &plot_path,
+        configuration_data.plot_size,
+        &title,
+        &Plot::Benchmark(&quantiles, run_best_values.len()),
+    )
+}
+
+fn draw_comparison_values<DB: DrawingBackend>(
+    draw_area: &DrawingArea<DB, Shift>,
+    curves: &Vec<(String, Vec<f64>)>,
+) {
+    let iterations = curves
+        .iter()
+        .map(|(_, values)| values.len())
+        .max()
+        .unwrap_or(0);
+    let values: Vec<f64> = curves
+        .iter()
+        .flat_map(|(_, values)| values.iter().cloned())
+        .collect();
+    let (plot_min, plot_max) = get_plot_range(&values);
+
+    let mut ctx = ChartBuilder::on(draw_area)
+        .margin(30)
+        .set_label_area_size(LabelAreaPosition::Bottom, 40)
+        .set_label_area_size(LabelAreaPosition::Left, 50)
+        .build_cartesian_2d(0..iterations, plot_min..plot_max)
+        .unwrap();
+
+    ctx.configure_mesh()
+        .light_line_style(WHITE)
+        .x_desc("iteration")
+        .y_desc("mean best value")
+        .draw()
+        .unwrap();
+
+    for (index, (name, values)) in curves.iter().enumerate() {
+        let color = Palette99::pick(index).to_rgba();
+        ctx.draw_series(LineSeries::new(
+            (0..values.len()).map(|x| (x, values[x])),
+            &color,
+        ))
+        .unwrap()
+        .label(name)
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    draw_series_labels(&mut ctx, SeriesLabelPosition::LowerRight);
+}
+
+/// Draws the mean best value per iteration of every compared variant into one plot.
+pub fn draw_comparison_plot(
+    configuration_data: &ConfigurationData,
+    curves: &Vec<(String, Vec<f64>)>,
+) -> Result<(), String> {
+    if curves.iter().all(|(_, values)| values.is_empty()) {
+        return Ok(());
+    }
+    let plot_path = build_plot_path(configuration_data, "compare", None)?;
+    let title = build_plot_title(configuration_data, None);
+    write_plot(
+        configuration_data,
+        &plot_path,
+        configuration_data.plot_size,
+        &title,
+        &Plot::Comparison(curves),
+    )
+}
+
+/// Color of a phase, green for the main road and red for the side road.
+fn phase_color(phase: &Phase, index: usize) -> RGBColor {
+    if is_main_phase(phase) {
+        GREEN
+    } else if is_side_phase(phase) {
+        RED
+    } else {
+        let (r, g, b) = Palette99::COLORS[index % Palette99::COLORS.len()];
+        RGBColor(r, g, b)
+    }
+}
+
+fn draw_time_space_values<DB: DrawingBackend>(
+    draw_area: &DrawingArea<DB, Shift>,
+    data: &TimeSpaceData,
+) {
+    let plan = &data.plan;
+    let trace = data.trace;
+    let intersections = plan.len();
+    let timesteps = trace.passed.len();
+
+    let mut ctx = ChartBuilder::on(draw_area)
+        .margin(30)
+        .set_label_area_size(LabelAreaPosition::Bottom, 40)
+        .set_label_area_size(LabelAreaPosition::Left, 50)
+        .build_cartesian_2d(
+            0.0..(timesteps as f64 + 1.0),
+            -0.5..(intersections as f64 - 0.5),
+        )
+        .unwrap();
+
+    ctx.configure_mesh()
+        .disable_mesh()
+        .y_labels(intersections)
+        .x_label_formatter(&|x| format!("{}", x.round()))
+        .y_label_formatter(&|y| format!("{}", y.round()))
+        .x_desc("timestep")
+        .y_desc("intersection")
+        .draw()
+        .unwrap();
+
+    // Intersections can switch between different phases, every distinct phase gets one color
+    let mut distinct: Vec<&Phase> = Vec::new();
+    for phase in data.phases.iter().flatten() {
+        if !distinct.contains(&phase) {
+            distinct.push(phase);
+        }
+    }
+    for (index, phase) in distinct.into_iter().enumerate() {
+        let color = phase_color(phase, index);
+        ctx.draw_series(
+            plan.iter()
+                .enumerate()
+                .flat_map(|(intersection, phases)| {
+                    let intersection_phases = &data.phases[intersection];
+                    (0..timesteps)
+                        .filter(move |t| intersection_phases[phases[*t]] == *phase)
+                        .map(move |t| (intersection as f64, t as f64))
+                })
+                .map(|(y, x)| Rectangle::new([(x, y - 0.12), (x + 1.0, y + 0.12)], color.filled())),
+        )
+        .unwrap()
+        .label(format!("{} green", format_phase(phase)))
+        .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], color.filled()));
+    }
+
+    if data.platoons {
+        let max_cars = trace
+            .passed
+            .iter()
+            .flat_map(|step| {
+                step.iter()
+                    .map(|passed| passed.main_from_prev.max(passed.main_from_next))
+            })
+            .max()
+            .unwrap_or(0)
+            .max(1) as f64;
+        // Cars from the previous intersection drive to the next one and the other way around
+        let directions: [(&str, usize, RGBColor); 2] = [
+            ("platoons to next intersection", 0, BLUE),
+            ("platoons to previous intersection", 1, MAGENTA),
+        ];
+        for (name, approach, color) in directions {
+            let mut lines: Vec<PathElement<(f64, f64)>> = Vec::new();
+            for (t, step) in trace.passed.iter().enumerate() {
+                for link in data.links.iter() {
+                    let cars = get_cars(&step[link.from], approach);
+                    if cars == 0 || link.from_approach != approach || link.to_approach != approach {
+                        continue;
+                    }
+                    let width = 1 + (4.0 * cars as f64 / max_cars).round() as u32;
+                    lines.push(PathElement::new(
+                        vec![
+                            (t as f64 + 0.5, link.from as f64),
+                            (t as f64 + 1.5, link.to as f64),
+                        ],
+                        color.mix(0.6).stroke_width(width),
+                    ));
+                }
+            }
+            ctx.draw_series(lines)
+                .unwrap()
+                .label(name)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        }
+    }
+    draw_series_labels(&mut ctx, SeriesLabelPosition::UpperRight);
+}
+
+/// Draws the green phases of every intersection over time, green bars for the main road and red
+/// bars for the side road. With platoons, the cars passing an intersection along the main road are
+/// drawn as lines to the neighbouring intersection they reach in the next timestep, thicker for
+/// more cars.
+pub fn draw_time_space_diagram(
+    configuration_data: &ConfigurationData,
+    optimization_data: &OptimizationData,
+    generation_data: &GenerationData,
+    simulation_data: &SimulationData,
+    candidate: &Vec<BitVec>,
+    trace: &SimulationTrace,
+) -> Result<(), String> {
+    if candidate.is_empty() || trace.passed.is_empty() {
+        return Ok(());
+    }
+    let plot_path = build_plot_path(configuration_data, "time-space", None)?;
+    let title = build_plot_title(configuration_data, Some(optimization_data));
+    let data = TimeSpaceData {
+        plan: decode_plan(candidate, generation_data),
+        phases: &generation_data.phases,
+        links: &simulation_data.links,
+        trace,
+        platoons: configuration_data.platoons,
+    };
+    write_plot(
+        configuration_data,
+        &plot_path,
+        configuration_data.plot_size,
+        &title,
+        &Plot::TimeSpace(data),
+    )
+}
+
+/// Number of cars waiting at one approach of an intersection.
+type QueueLength = fn(&TrafficState) -> i32;
+
+/// Color of a queue from white for no cars to dark red for the longest queue.
+fn queue_color(cars: i32, max_cars: i32) -> RGBColor {
+    let share = cars as f64 / max_cars as f64;
+    RGBColor(
+        (255.0 - 105.0 * share) as u8,
+        (255.0 * (1.0 - share)) as u8,
+        (255.0 * (1.0 - share)) as u8,
+    )
+}
+
+fn draw_queue_values<DB: DrawingBackend>(
+    draw_area: &DrawingArea<DB, Shift>,
+    traces: &Vec<(String, SimulationTrace)>,
+) {
+    let timesteps = traces[0].1.passed.len();
+    let intersections = traces[0].1.states[0].len();
+    let approaches: [(&str, QueueLength); 3] = [
+        ("main_from_prev", |state| state.main_from_prev),
+        ("main_from_next", |state| state.main_from_next),
+        ("side", |state| state.side),
+    ];
+    let max_cars = traces
+        .iter()
+        .flat_map(|(_, trace)| trace.states[..timesteps].iter().flatten())
+        .map(|state| {
+            state
+                .main_from_prev
+                .max(state.main_from_next)
+                .max(state.side)
+        })
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let draw_area = draw_area
+        .titled(
+            &format!("Queue lengths from white 0 to dark red {} cars", max_cars),
+            ("sans-serif", 14),
+        )
+        .unwrap();
+    let areas = draw_area.split_evenly((approaches.len(), traces.len()));
+
+    for (row, (approach, queue)) in approaches.iter().enumerate() {
+        for (column, (name, trace)) in traces.iter().enumerate() {
+            let mut ctx = ChartBuilder::on(&areas[row * traces.len() + column])
+                .margin(20)
+                .caption(format!("{} ({})", approach, name), ("sans-serif", 14))
+                .set_label_area_size(LabelAreaPosition::Bottom, 40)
+                .set_label_area_size(LabelAreaPosition::Left, 50)
+                .build_cartesian_2d(0..timesteps, 0..intersections)
+                .unwrap();
+
+            ctx.configure_mesh()
+                .disable_mesh()
+                .x_desc("timestep")
+                .y_desc("intersection")
+                .draw()
+                .unwrap();
+
+            ctx.draw_series((0..timesteps).flat_map(|t| {
+                (0..intersections).map(move |intersection| {
+                    let cars = queue(&trace.states[t][intersection]);
+                    Rectangle::new(
+                        [(t, intersection), (t + 1, intersection + 1)],
+                        queue_color(cars, max_cars).filled(),
+                    )
+                })
+            }))
+            .unwrap();
+        }
+    }
+}
+
+/// Draws the queue lengths at the start of every timestep per intersection, one row of heatmaps
+/// for each approach and one column for each named trace, all with the same color scale. The
+/// plot size applies to each column.
+pub fn draw_queue_heatmaps(
+    configuration_data: &ConfigurationData,
+    optimization_data: &OptimizationData,
+    traces: &Vec<(String, SimulationTrace)>,
+) -> Result<(), String> {
+    if traces[0].1.passed.is_empty() || traces[0].1.states[0].is_empty() {
+        return Ok(());
+    }
+    let plot_path = build_plot_path(configuration_data, "queues", None)?;
+    let title = build_plot_title(configuration_data, Some(optimization_data));
+    let (width, height) = configuration_data.plot_size;
+    let size = (width * traces.len() as u32, height);
+    write_plot(
+        configuration_data,
+        &plot_path,
+        size,
+        &title,
+        &Plot::Queues(traces),
+    )
+}
@@ -0,0 +1,191 @@
+use crate::data::GenerationData;
+use crate::data::Phase;
+use crate::data::SimulationData;
+use crate::data::TrafficState;
+use crate::phases::decode_plan;
+use crate::phases::format_phase;
+use crate::phases::{is_main_phase, is_side_phase};
+use crate::simulation::simulate_cars;
+use crate::simulation::trace_simulation;
+use bit_vec::BitVec;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+pub const SIMULATION_VIEWS: [&str; 3] = ["steps", "animate", "raw"];
+
+const MAIN_GREEN: char = '=';
+const SIDE_GREEN: char = '|';
+const BAR_WIDTH: usize = 10;
+const ANIMATION_DELAY: Duration = Duration::from_millis(500);
+
+/// Symbol of a phase, phases serving other movements than the whole main road or the side road
+/// are shown by their index.
+fn phase_symbol(phases: &Vec<Phase>, index: usize) -> char {
+    let phase = &phases[index];
+    if is_main_phase(phase) {
+        MAIN_GREEN
+    } else if is_side_phase(phase) {
+        SIDE_GREEN
+    } else {
+        char::from_digit(index as u32, 36).unwrap_or('?')
+    }
+}
+
+/// Line of timestep numbers above the plan, one column per timestep.
+fn render_timestep_header(timesteps: usize) -> String {
+    let mut header = String::new();
+    for t in (0..timesteps).step_by(5) {
+        let label = t.to_string();
+        header.push_str(&format!("{:<5}", label));
+    }
+    header.trim_end().to_string()
+}
+
+fn render_legend(phases: &Vec<Phase>) -> String {
+    let legend: Vec<String> = (0..phases.len())
+        .map(|index| {
+            format!(
+                "{} {} green",
+                phase_symbol(phases, index),
+                format_phase(&phases[index])
+            )
+        })
+        .collect();
+    legend.join(", ")
+}
+
+/// Renders the plan as one row of phase symbols per intersection and one column per timestep.
+/// Intersections switching between different phases get their own legend behind their row.
+pub fn render_plan(candidate: &Vec<BitVec>, generation_data: &GenerationData) -> String {
+    let phases = &generation_data.phases;
+    let shared = phases.iter().all(|intersection| *intersection == phases[0]);
+    let mut plan = if shared {
+        format!("Signal plan ({}):\n", render_legend(&phases[0]))
+    } else {
+        String::from("Signal plan:\n")
+    };
+    plan.push_str(&format!(
+        "{:>4}  {}\n",
+        "",
+        render_timestep_header(generation_data.timesteps)
+    ));
+    for (intersection, row) in decode_plan(candidate, generation_data).iter().enumerate() {
+        let row: String = row
+            .iter()
+            .map(|index| phase_symbol(&phases[intersection], *index))
+            .collect();
+        if shared {
+            plan.push_str(&format!("{:>4}  {}\n", intersection, row));
+        } else {
+            plan.push_str(&format!(
+                "{:>4}  {}  ({})\n",
+                intersection,
+                row,
+                render_legend(&phases[intersection])
+            ));
+        }
+    }
+    plan
+}
+
+fn render_queue(cars: i32, max_cars: i32) -> String {
+    let length = (BAR_WIDTH as f64 * cars as f64 / max_cars as f64).round() as usize;
+    format!(
+        "{:>3} {:<width$}",
+        cars,
+        "#".repeat(length),
+        width = BAR_WIDTH
+    )
+}
+
+fn render_step(
+    t: usize,
+    plan: &Vec<Vec<usize>>,
+    phases: &Vec<Vec<Phase>>,
+    state: &Vec<TrafficState>,
+    passed: &Vec<TrafficState>,
+    blocked_cars: i32,
+    max_cars: i32,
+) -> String {
+    let mut step = format!("Step {}:\n", t);
+    step.push_str(&format!(
+        "{:>4} {} {:<14} {:<14} {:<14} {}\n",
+        "", "light", "main_from_prev", "main_from_next", "side", "passed"
+    ));
+    for (intersection, traffic) in state.iter().enumerate() {
+        step.push_str(&format!(
+            "{:>4} {:^5} {:<14} {:<14} {:<14} {:>3}\n",
+            intersection,
+            phase_symbol(&phases[intersection], plan[intersection][t]),
+            render_queue(traffic.main_from_prev, max_cars),
+            render_queue(traffic.main_from_next, max_cars),
+            render_queue(traffic.side, max_cars),
+            passed[intersection].main_from_prev
+                + passed[intersection].main_from_next
+                + passed[intersection].side
+        ));
+    }
+    if blocked_cars > 0 {
+        step.push_str(&format!("{} cars held back by full links\n", blocked_cars));
+    }
+    step
+}
+
+/// Prints the plan and the queues at every intersection in every step of its simulation, either
+/// all steps after each other or animated in place. The raw view prints the traffic states.
+pub fn print_simulation(
+    candidate: &Vec<BitVec>,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    view: &str,
+) {
+    if view == "raw" {
+        simulate_cars(candidate, simulation_data, generation_data, true);
+        return;
+    }
+    let trace = trace_simulation(candidate, simulation_data, generation_data);
+    let max_cars = trace
+        .states
+        .iter()
+        .flatten()
+        .map(|traffic| {
+            traffic
+                .main_from_prev
+                .max(traffic.main_from_next)
+                .max(traffic.side)
+        })
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let phase_plan = decode_plan(candidate, generation_data);
+    let plan = render_plan(candidate, generation_data);
+    println!("{}", plan);
+    for (t, passed) in trace.passed.iter().enumerate() {
+        let step = render_step(
+            t,
+            &phase_plan,
+            &generation_data.phases,
+            &trace.states[t],
+            passed,
+            trace.blocked[t],
+            max_cars,
+        );
+        if view == "animate" {
+            // Clear the terminal and move the cursor to the top left before every step
+            print!("\x1b[2J\x1b[H{}\n{}", plan, step);
+            io::stdout().flush().unwrap();
+            thread::sleep(ANIMATION_DELAY);
+        } else {
+            println!("{}", step);
+        }
+    }
+    println!(
+        "driving_cars: {}, waiting_cars: {}",
+        trace.driving_cars, trace.waiting_cars
+    );
+    if let Some(t) = trace.gridlock_step {
+        println!("Gridlock in step {}, no car could pass", t);
+    }
+}
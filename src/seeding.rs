@@ -0,0 +1,158 @@
+use crate::data::parse_candidate;
+use crate::data::GenerationData;
+use crate::data::OptimizationData;
+use crate::phases::{check_plan, encode_main_side_plan};
+use crate::utils::distinct_random;
+use bit_vec::BitVec;
+use std::fs;
+
+pub fn generate_all_main_candidate(intersections: usize, timesteps: usize) -> Vec<BitVec> {
+    vec![BitVec::from_elem(timesteps, true); intersections]
+}
+
+/// Fixed cycle giving the main road green for the first half of every cycle. Each intersection
+/// is shifted by `offset` timesteps against the previous one, an offset of 1 lets a platoon
+/// driving from the first to the last intersection hit green everywhere.
+pub fn generate_cycle_candidate(
+    intersections: usize,
+    timesteps: usize,
+    cycle_length: usize,
+    offset: i64,
+) -> Vec<BitVec> {
+    let mut candidate: Vec<BitVec> = Vec::with_capacity(intersections);
+    for index in 0..intersections {
+        let mut data = BitVec::with_capacity(timesteps);
+        for t in 0..timesteps {
+            let position = (t as i64 - index as i64 * offset).rem_euclid(cycle_length as i64);
+            data.push(position < (cycle_length / 2) as i64);
+        }
+        candidate.push(data);
+    }
+    candidate
+}
+
+/// Splits the text of a candidate file into blocks separated by lines that are empty or only
+/// hold whitespace, with LF or CRLF line endings.
+fn split_blocks(content: &str) -> Vec<String> {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut block: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                blocks.push(block.join("\n"));
+                block.clear();
+            }
+        } else {
+            block.push(line);
+        }
+    }
+    if !block.is_empty() {
+        blocks.push(block.join("\n"));
+    }
+    blocks
+}
+
+/// Loads all candidates of a file, candidates are separated by empty lines.
+pub fn load_candidates(
+    path: &str,
+    intersections: usize,
+    bits: usize,
+) -> Result<Vec<Vec<BitVec>>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|error| format!("Could not read candidates from {}: {}", path, error))?;
+
+    let mut candidates: Vec<Vec<BitVec>> = Vec::new();
+    for block in split_blocks(&content).iter() {
+        candidates.push(parse_candidate(block, intersections, bits)?);
+    }
+    Ok(candidates)
+}
+
+pub fn generate_seeds(
+    seeding: &Vec<String>,
+    cycle_lengths: &Vec<usize>,
+    seed_file: &Option<String>,
+    generation_data: &GenerationData,
+) -> Result<Vec<Vec<BitVec>>, String> {
+    let intersections = generation_data.intersections;
+    let timesteps = generation_data.timesteps;
+    let bits = timesteps * generation_data.phase_bits;
+    let mut seeds: Vec<Vec<BitVec>> = Vec::new();
+
+    for variant in seeding.iter() {
+        if variant == "all_main" {
+            seeds.push(encode_main_side_plan(
+                &generate_all_main_candidate(intersections, timesteps),
+                generation_data,
+            ));
+        } else if variant == "cycles" || variant == "green_wave" {
+            for cycle_length in cycle_lengths.iter() {
+                if *cycle_length < 2 {
+                    return Err(format!("Cycle length {} is too short", cycle_length));
+                }
+                if variant == "cycles" {
+                    seeds.push(encode_main_side_plan(
+                        &generate_cycle_candidate(intersections, timesteps, *cycle_length, 0),
+                        generation_data,
+                    ));
+                } else {
+                    seeds.push(encode_main_side_plan(
+                        &generate_cycle_candidate(intersections, timesteps, *cycle_length, 1),
+                        generation_data,
+                    ));
+                    seeds.push(encode_main_side_plan(
+                        &generate_cycle_candidate(intersections, timesteps, *cycle_length, -1),
+                        generation_data,
+                    ));
+                }
+            }
+        } else if variant == "file" {
+            match seed_file {
+                Some(path) => {
+                    for candidate in load_candidates(path, intersections, bits)? {
+                        check_plan(&candidate, generation_data)?;
+                        seeds.push(candidate);
+                    }
+                }
+                None => return Err(String::from("Seeding from file needs a seed file")),
+            }
+        }
+    }
+    Ok(seeds)
+}
+
+/// Replaces the first candidates of the population with seeds, at most the seed fraction of the
+/// population. If there are more seeds than places, a random selection of them is used.
+/// Returns the number of seeded candidates.
+pub fn seed_population(
+    population: &mut Vec<Vec<BitVec>>,
+    optimization_data: &OptimizationData,
+) -> usize {
+    let seeds = &optimization_data.seed_candidates;
+    let places = (((population.len() as f64) * optimization_data.seed_fraction).round() as usize)
+        .min(population.len());
+
+    let selected: Vec<usize> = if seeds.len() > places {
+        distinct_random(0, seeds.len(), places)
+    } else {
+        (0..seeds.len()).collect()
+    };
+    for (index, seed) in selected.iter().enumerate() {
+        population[index] = seeds[*seed].clone();
+    }
+    selected.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_are_separated_by_empty_lines() {
+        let expected = vec![String::from("01,10"), String::from("11,\n00")];
+        assert_eq!(split_blocks("01,10\n\n11,\n00\n"), expected);
+        assert_eq!(split_blocks("01,10\r\n\r\n11,\r\n00\r\n"), expected);
+        assert_eq!(split_blocks("\n01,10\n  \t\n\n11,\n00"), expected);
+        assert!(split_blocks(" \r\n").is_empty());
+    }
+}
@@ -1,11 +1,21 @@
+use crate::arrivals::get_robust_value;
 use crate::data::build_empty_traffic_state;
+use crate::data::calculate_green_share;
 use crate::data::calculate_increased_max_passthrough;
 use crate::data::GenerationData;
+use crate::data::Link;
 use crate::data::OptimizationData;
+use crate::data::Phase;
 use crate::data::SimulationData;
+use crate::data::SimulationTrace;
 use crate::data::TrafficState;
+use crate::network::{get_cars, get_cars_mut, has_link_capacities, APPROACHES};
+use crate::phases::{decode_phase, get_approach_turns, serves_approach};
 use bit_vec::BitVec;
 use std::cmp::min;
+use std::collections::VecDeque;
+
+pub const FITNESS_VALUES: [&str; 4] = ["ratio", "difference", "driving_cars", "waiting_cars"];
 
 fn extract_step(traffic_data: &Vec<Vec<TrafficState>>, t: usize) -> Vec<TrafficState> {
     let mut step_data: Vec<TrafficState> = Vec::new();
@@ -18,193 +28,502 @@ fn extract_step(traffic_data: &Vec<Vec<TrafficState>>, t: usize) -> Vec<TrafficS
     step_data
 }
 
+fn build_empty_step(intersections: usize) -> Vec<TrafficState> {
+    (0..intersections)
+        .map(|_| build_empty_traffic_state())
+        .collect()
+}
+
 fn calc_next(val: i32, fac: f64) -> i32 {
     ((val as f64) * fac).floor() as i32
 }
 
-fn apply_main(
-    traffic_to_update: &mut Vec<TrafficState>,
-    current_traffic: &TrafficState,
-    index: usize,
-    driving_cars: &mut i32,
-    waiting_cars: &mut i32,
+/// Cars of a movement passing in one step, all waiting cars if the movement has green. Only the
+/// share of the step left for green limits the cars after switching.
+fn served_cars(
+    waiting: i32,
+    green: bool,
     max_passthrough: i32,
+    green_share: f64,
     simulation_data: &SimulationData,
-) {
-    let main_from_prev;
-    let main_from_next;
-    if !simulation_data.disable_max_passthrough {
-        main_from_prev = min(max_passthrough, current_traffic.main_from_prev);
-        main_from_next = min(max_passthrough, current_traffic.main_from_next);
+) -> i32 {
+    if !green {
+        0
+    } else if !simulation_data.disable_max_passthrough {
+        min(calc_next(max_passthrough, green_share), waiting)
     } else {
-        main_from_prev = current_traffic.main_from_prev;
-        main_from_next = current_traffic.main_from_next;
+        calc_next(waiting, green_share)
     }
+}
 
-    *driving_cars += main_from_prev;
-    *driving_cars += main_from_next;
+/// Max passthrough of every approach after a timestep without a phase change.
+fn increase_max_passthrough(max_passthrough: &TrafficState) -> TrafficState {
+    TrafficState {
+        main_from_prev: calculate_increased_max_passthrough(max_passthrough.main_from_prev),
+        main_from_next: calculate_increased_max_passthrough(max_passthrough.main_from_next),
+        side: calculate_increased_max_passthrough(max_passthrough.side),
+    }
+}
 
-    *waiting_cars += current_traffic.main_from_prev - main_from_prev;
-    *waiting_cars += current_traffic.main_from_next - main_from_next;
-    *waiting_cars += current_traffic.side;
+/// Share of the cars of an approach taking a turn the phase gives green, all of them if every
+/// turn of the approach has green.
+fn green_turn_share(phase: &Phase, approach: usize, turn_shares: &Vec<f64>) -> f64 {
+    if serves_approach(phase, approach) {
+        return 1.0;
+    }
+    get_approach_turns(approach)
+        .iter()
+        .filter(|turn| phase.turns[**turn])
+        .map(|turn| turn_shares[*turn])
+        .sum()
+}
 
-    match traffic_to_update.get_mut(index) {
-        Some(next_traffic_current) => {
-            next_traffic_current.side += current_traffic.side;
-            next_traffic_current.main_from_prev += current_traffic.main_from_prev - main_from_prev;
-            next_traffic_current.main_from_next += current_traffic.main_from_next - main_from_next;
+/// Cars of all movements served by the phase passing in one step, of an approach only the cars
+/// taking a turn with green.
+fn serve_phase(
+    current_traffic: &TrafficState,
+    phase: &Phase,
+    turn_shares: &Vec<f64>,
+    max_passthrough: &TrafficState,
+    green_share: f64,
+    simulation_data: &SimulationData,
+) -> TrafficState {
+    let mut passed = build_empty_traffic_state();
+    for approach in 0..APPROACHES.len() {
+        let share = green_turn_share(phase, approach, turn_shares);
+        let mut waiting = get_cars(current_traffic, approach);
+        if share < 1.0 {
+            waiting = calc_next(waiting, share);
         }
-        None => {}
+        *get_cars_mut(&mut passed, approach) = served_cars(
+            waiting,
+            share > 0.0,
+            get_cars(max_passthrough, approach),
+            green_share,
+            simulation_data,
+        );
     }
-    match traffic_to_update.get_mut(index + 1) {
-        Some(next_traffic_next) => {
-            next_traffic_next.main_from_prev +=
-                calc_next(main_from_prev, simulation_data.main_percentage)
-        }
-        None => {}
+    passed
+}
+
+/// Share of the passing cars of its approach every link takes under the shown phases, the cars
+/// of a turn with red do not pass, so the links of the turns with green take all passing cars.
+fn get_link_ratios(
+    links: &Vec<Link>,
+    phases: &Vec<&Phase>,
+    turn_shares: &Vec<Vec<f64>>,
+) -> Vec<f64> {
+    links
+        .iter()
+        .map(|link| {
+            let phase = phases[link.from];
+            let share = green_turn_share(phase, link.from_approach, &turn_shares[link.from]);
+            if share >= 1.0 {
+                link.ratio
+            } else if phase.turns[link.turn] {
+                link.ratio / share
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Most of `cars` that can pass when the share `percentage` of them drives into a link with
+/// `space` free places.
+fn limit_inflow(cars: i32, space: i32, percentage: f64) -> i32 {
+    if percentage <= 0.0 {
+        return cars;
     }
-    if index > 0 {
-        match traffic_to_update.get_mut(index - 1) {
-            Some(next_traffic_prev) => {
-                next_traffic_prev.main_from_next +=
-                    calc_next(main_from_next, simulation_data.main_percentage)
+    let limit = (((space + 1) as f64) / percentage).ceil() as i32 - 1;
+    cars.min(limit).max(0)
+}
+
+/// Holds back cars that would drive into a full link, the approaches of an intersection in the
+/// order of `APPROACHES`, so cars of the main road before those turning in from the side road.
+/// The free places of a link are its capacity minus the arriving cars, the cars staying in the
+/// approach it leads to, the cars still driving towards it and the cars already let into it, so
+/// blocking spreads upstream until no more cars are held back. Returns the number of held back
+/// cars.
+fn limit_spillback(
+    passed: &mut Vec<TrafficState>,
+    current_traffic: &Vec<TrafficState>,
+    arrivals: &Vec<TrafficState>,
+    in_transit: &VecDeque<Vec<TrafficState>>,
+    links: &Vec<Link>,
+    ratios: &Vec<f64>,
+) -> i32 {
+    let served: i32 = passed
+        .iter()
+        .map(|cars| cars.main_from_prev + cars.main_from_next + cars.side)
+        .sum();
+    loop {
+        let mut changed = false;
+        let mut entering = build_empty_step(passed.len());
+        for index in 0..passed.len() {
+            let mut limited = passed[index].clone();
+            for approach in 0..APPROACHES.len() {
+                let outgoing: Vec<(&Link, f64)> = links
+                    .iter()
+                    .zip(ratios.iter().cloned())
+                    .filter(|(link, _)| link.from == index && link.from_approach == approach)
+                    .collect();
+                let mut cars = get_cars(&limited, approach);
+                for (link, ratio) in outgoing.iter() {
+                    let capacity = match link.capacity {
+                        Some(capacity) => capacity,
+                        None => continue,
+                    };
+                    let staying = get_cars(&current_traffic[link.to], link.to_approach)
+                        - get_cars(&passed[link.to], link.to_approach);
+                    let driving: i32 = in_transit
+                        .iter()
+                        .map(|traffic| get_cars(&traffic[link.to], link.to_approach))
+                        .sum();
+                    let space = capacity
+                        - get_cars(&arrivals[link.to], link.to_approach)
+                        - staying
+                        - driving
+                        - get_cars(&entering[link.to], link.to_approach);
+                    cars = limit_inflow(cars, space, *ratio);
+                }
+                *get_cars_mut(&mut limited, approach) = cars;
+                for (link, ratio) in outgoing.iter() {
+                    *get_cars_mut(&mut entering[link.to], link.to_approach) +=
+                        calc_next(cars, *ratio);
+                }
             }
-            None => {}
+            if limited.main_from_prev != passed[index].main_from_prev
+                || limited.main_from_next != passed[index].main_from_next
+                || limited.side != passed[index].side
+            {
+                passed[index] = limited;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
         }
     }
+    served
+        - passed
+            .iter()
+            .map(|cars| cars.main_from_prev + cars.main_from_next + cars.side)
+            .sum::<i32>()
 }
 
-fn apply_side(
+/// Counts the passing and the waiting cars of an intersection, the waiting cars stay in its
+/// queues for the next timestep.
+fn apply_passed(
     traffic_to_update: &mut Vec<TrafficState>,
     current_traffic: &TrafficState,
+    passed: &TrafficState,
     index: usize,
     driving_cars: &mut i32,
     waiting_cars: &mut i32,
-    max_passthrough: i32,
-    simulation_data: &SimulationData,
 ) {
-    let side;
-    if !simulation_data.disable_max_passthrough {
-        side = min(max_passthrough, current_traffic.side);
-    } else {
-        side = current_traffic.side;
-    }
+    *driving_cars += passed.main_from_prev;
+    *driving_cars += passed.main_from_next;
+    *driving_cars += passed.side;
 
-    *driving_cars += side;
-
-    *waiting_cars += current_traffic.side - side;
-    *waiting_cars += current_traffic.main_from_prev;
-    *waiting_cars += current_traffic.main_from_next;
+    *waiting_cars += current_traffic.main_from_prev - passed.main_from_prev;
+    *waiting_cars += current_traffic.main_from_next - passed.main_from_next;
+    *waiting_cars += current_traffic.side - passed.side;
 
     match traffic_to_update.get_mut(index) {
         Some(next_traffic_current) => {
-            next_traffic_current.side += current_traffic.side - side;
-            next_traffic_current.main_from_prev += current_traffic.main_from_prev;
-            next_traffic_current.main_from_next += current_traffic.main_from_next;
+            next_traffic_current.side += current_traffic.side - passed.side;
+            next_traffic_current.main_from_prev +=
+                current_traffic.main_from_prev - passed.main_from_prev;
+            next_traffic_current.main_from_next +=
+                current_traffic.main_from_next - passed.main_from_next;
         }
         None => {}
     }
-    match traffic_to_update.get_mut(index + 1) {
-        Some(next_traffic_next) => {
-            next_traffic_next.main_from_prev +=
-                calc_next(side, simulation_data.side_percentage / 2.0)
+}
+
+/// Lets the passing cars drive along the links to the next intersections, where they arrive
+/// after the travel time of the link, each link taking its share of `ratios`. Cars not taken by
+/// any link leave the network.
+fn route_passed(
+    in_transit: &mut VecDeque<Vec<TrafficState>>,
+    passed: &Vec<TrafficState>,
+    links: &Vec<Link>,
+    ratios: &Vec<f64>,
+) {
+    for (link, ratio) in links.iter().zip(ratios.iter()) {
+        if let Some(next_traffic) = in_transit[link.travel_time - 1].get_mut(link.to) {
+            *get_cars_mut(next_traffic, link.to_approach) +=
+                calc_next(get_cars(&passed[link.from], link.from_approach), *ratio);
         }
-        None => {}
     }
-    if index > 0 {
-        match traffic_to_update.get_mut(index - 1) {
-            Some(next_traffic_prev) => {
-                next_traffic_prev.main_from_next +=
-                    calc_next(side, simulation_data.side_percentage / 2.0)
-            }
-            None => {}
-        }
+}
+
+/// Traffic carried from one timestep to the next, the cars waiting at the intersections, the
+/// cars that passed them in the last timestep, the cars driving along the links by the number of
+/// timesteps until they arrive and the total number of driving and waiting cars.
+struct SimulationState {
+    current_traffic: Vec<TrafficState>,
+    passed: Vec<TrafficState>,
+    in_transit: VecDeque<Vec<TrafficState>>,
+    driving_cars: i32,
+    waiting_cars: i32,
+}
+
+fn build_simulation_state(
+    simulation_data: &SimulationData,
+    traffic_data: &Vec<Vec<TrafficState>>,
+) -> SimulationState {
+    let current_traffic = extract_step(traffic_data, 0);
+    let max_travel_time = simulation_data
+        .links
+        .iter()
+        .map(|link| link.travel_time)
+        .max()
+        .unwrap_or(1);
+    let in_transit: VecDeque<Vec<TrafficState>> = (0..max_travel_time)
+        .map(|_| build_empty_step(current_traffic.len()))
+        .collect();
+    SimulationState {
+        passed: Vec::with_capacity(current_traffic.len()),
+        current_traffic,
+        in_transit,
+        driving_cars: 0,
+        waiting_cars: 0,
     }
 }
 
+/// Simulates timestep `t`, moves the state to the next timestep and returns the number of cars
+/// held back by full links.
 fn step(
     simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    traffic_data: &Vec<Vec<TrafficState>>,
     traffic_lights: &Vec<BitVec>,
-    current_traffic: &Vec<TrafficState>,
     t: usize,
-    driving_cars: &mut i32,
-    waiting_cars: &mut i32,
-) -> Vec<TrafficState> {
-    let mut next_traffic = extract_step(&simulation_data.traffic_data, t + 1);
+    state: &mut SimulationState,
+) -> i32 {
+    let mut next_traffic = extract_step(traffic_data, t + 1);
+    let current_traffic = &state.current_traffic;
+    let passed = &mut state.passed;
+    let in_transit = &mut state.in_transit;
+    passed.clear();
+    let mut phases: Vec<&Phase> = Vec::with_capacity(current_traffic.len());
     for (index, traffic) in current_traffic.iter().enumerate() {
-        let mut max_passthrough = simulation_data.max_passthrough;
-        if !simulation_data.disable_increasing_passthrough
-            && t > 0
-            && (traffic_lights[index][t - 1] == traffic_lights[index][t])
-        {
-            max_passthrough = calculate_increased_max_passthrough(simulation_data.max_passthrough);
+        let phase = decode_phase(&traffic_lights[index], index, t, generation_data);
+        let switched =
+            t > 0 && decode_phase(&traffic_lights[index], index, t - 1, generation_data) != phase;
+        let mut max_passthrough = simulation_data.max_passthroughs[index].clone();
+        let mut green_share = 1.0;
+        if !simulation_data.disable_increasing_passthrough && t > 0 && !switched {
+            max_passthrough = increase_max_passthrough(&max_passthrough);
         }
-        match traffic_lights[index][t] {
-            true => apply_main(
-                &mut next_traffic,
-                traffic,
-                index,
-                driving_cars,
-                waiting_cars,
-                max_passthrough,
-                simulation_data,
-            ),
-            false => apply_side(
-                &mut next_traffic,
-                traffic,
-                index,
-                driving_cars,
-                waiting_cars,
-                max_passthrough,
-                simulation_data,
-            ),
+        if switched {
+            green_share = calculate_green_share(simulation_data);
         }
+        phases.push(&generation_data.phases[index][phase]);
+        passed.push(serve_phase(
+            traffic,
+            phases[index],
+            &simulation_data.turn_shares[index],
+            &max_passthrough,
+            green_share,
+            simulation_data,
+        ));
+    }
+    let ratios = get_link_ratios(
+        &simulation_data.links,
+        &phases,
+        &simulation_data.turn_shares,
+    );
+    let blocked_cars = if has_link_capacities(&simulation_data.links) {
+        limit_spillback(
+            passed,
+            current_traffic,
+            &next_traffic,
+            in_transit,
+            &simulation_data.links,
+            &ratios,
+        )
+    } else {
+        0
+    };
+    for (index, traffic) in current_traffic.iter().enumerate() {
+        apply_passed(
+            &mut next_traffic,
+            traffic,
+            &passed[index],
+            index,
+            &mut state.driving_cars,
+            &mut state.waiting_cars,
+        );
     }
-    next_traffic
+    route_passed(in_transit, passed, &simulation_data.links, &ratios);
+    let arriving = in_transit.pop_front().unwrap();
+    for (traffic, cars) in next_traffic.iter_mut().zip(arriving.iter()) {
+        traffic.main_from_prev += cars.main_from_prev;
+        traffic.main_from_next += cars.main_from_next;
+        traffic.side += cars.side;
+    }
+    in_transit.push_back(build_empty_step(current_traffic.len()));
+    state.current_traffic = next_traffic;
+    blocked_cars
 }
 
-fn fitness(optimization_data: &OptimizationData, driving_cars: i32, waiting_cars: i32) -> f64 {
-    if optimization_data.fitness_value == "difference" {
+pub fn fitness(fitness_value: &str, driving_cars: i32, waiting_cars: i32) -> f64 {
+    if fitness_value == "difference" {
         return (driving_cars - waiting_cars) as f64;
-    } else if optimization_data.fitness_value == "ratio" {
+    } else if fitness_value == "ratio" {
         return (driving_cars as f64) / (waiting_cars as f64);
-    } else if optimization_data.fitness_value == "driving_cars" {
+    } else if fitness_value == "driving_cars" {
         return driving_cars as f64;
-    } else if optimization_data.fitness_value == "waiting_cars" {
+    } else if fitness_value == "waiting_cars" {
         return -waiting_cars as f64;
     }
     0.0
 }
 
-pub fn simulate(
+/// Runs the simulation on the given traffic data and calls `observe` after every step with the
+/// number of the step, the traffic afterwards, the cars that passed each intersection during it
+/// and the cars held back by full links. Returns the total number of driving and waiting cars.
+fn run_simulation<F: FnMut(usize, &Vec<TrafficState>, &Vec<TrafficState>, i32)>(
+    candidate: &Vec<BitVec>,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    traffic_data: &Vec<Vec<TrafficState>>,
+    mut observe: F,
+) -> (i32, i32) {
+    let mut state = build_simulation_state(simulation_data, traffic_data);
+    for t in 0..generation_data.timesteps {
+        let blocked_cars = step(
+            simulation_data,
+            generation_data,
+            traffic_data,
+            candidate,
+            t,
+            &mut state,
+        );
+        observe(t + 1, &state.current_traffic, &state.passed, blocked_cars);
+    }
+    (state.driving_cars, state.waiting_cars)
+}
+
+/// Runs the simulation and returns the total number of driving and waiting cars.
+pub fn simulate_cars(
     candidate: &Vec<BitVec>,
     simulation_data: &SimulationData,
-    optimization_data: &OptimizationData,
     generation_data: &GenerationData,
     print_simulation: bool,
-) -> f64 {
-    let mut driving_cars = 0;
-    let mut waiting_cars = 0;
-    let mut current_step = extract_step(&simulation_data.traffic_data, 0);
+) -> (i32, i32) {
     if print_simulation {
         println!("Step 0:");
-        println!("{:?}", current_step);
+        println!("{:?}", extract_step(&simulation_data.traffic_data, 0));
     }
-    for t in 0..generation_data.timesteps {
-        current_step = step(
-            simulation_data,
+    run_simulation(
+        candidate,
+        simulation_data,
+        generation_data,
+        &simulation_data.traffic_data,
+        |t, current_step, _, _| {
+            if print_simulation {
+                println!("Step {}:", t);
+                println!("{:?}", current_step);
+            }
+        },
+    )
+}
+
+/// Runs the simulation and records the traffic of every step, starting with the initial traffic,
+/// the cars that passed each intersection and the cars held back by full links in every step.
+/// The network is in gridlock in a step where cars are held back and no car passes at all.
+pub fn trace_simulation(
+    candidate: &Vec<BitVec>,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+) -> SimulationTrace {
+    let mut states = vec![extract_step(&simulation_data.traffic_data, 0)];
+    let mut passed: Vec<Vec<TrafficState>> = Vec::with_capacity(generation_data.timesteps);
+    let mut blocked: Vec<i32> = Vec::with_capacity(generation_data.timesteps);
+    let mut gridlock_step: Option<usize> = None;
+    let (driving_cars, waiting_cars) = run_simulation(
+        candidate,
+        simulation_data,
+        generation_data,
+        &simulation_data.traffic_data,
+        |t, current_step, passed_step, blocked_cars| {
+            let passed_cars: i32 = passed_step
+                .iter()
+                .map(|cars| cars.main_from_prev + cars.main_from_next + cars.side)
+                .sum();
+            if gridlock_step.is_none() && blocked_cars > 0 && passed_cars == 0 {
+                gridlock_step = Some(t - 1);
+            }
+            states.push(current_step.clone());
+            passed.push(passed_step.clone());
+            blocked.push(blocked_cars);
+        },
+    );
+    SimulationTrace {
+        states,
+        passed,
+        blocked,
+        gridlock_step,
+        driving_cars,
+        waiting_cars,
+    }
+}
+
+pub fn simulate(
+    candidate: &Vec<BitVec>,
+    simulation_data: &SimulationData,
+    optimization_data: &OptimizationData,
+    generation_data: &GenerationData,
+    print_simulation: bool,
+) -> f64 {
+    if simulation_data.samples.is_empty() || print_simulation {
+        let (driving_cars, waiting_cars) = simulate_cars(
             candidate,
-            &current_step,
-            t,
-            &mut driving_cars,
-            &mut waiting_cars,
+            simulation_data,
+            generation_data,
+            print_simulation,
         );
-        if print_simulation {
-            println!("Step {}:", t + 1);
-            println!("{:?}", current_step);
+        if simulation_data.samples.is_empty() {
+            return fitness(&optimization_data.fitness_value, driving_cars, waiting_cars);
         }
     }
-    fitness(optimization_data, driving_cars, waiting_cars)
+    get_robust_value(
+        &simulate_samples(
+            candidate,
+            simulation_data,
+            generation_data,
+            &optimization_data.fitness_value,
+        ),
+        &simulation_data.robust_fitness,
+        simulation_data.cvar_alpha,
+    )
+}
+
+/// Fitness values of the candidate on every sampled arrival realization.
+pub fn simulate_samples(
+    candidate: &Vec<BitVec>,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    fitness_value: &str,
+) -> Vec<f64> {
+    simulation_data
+        .samples
+        .iter()
+        .map(|traffic_data| {
+            let (driving_cars, waiting_cars) = run_simulation(
+                candidate,
+                simulation_data,
+                generation_data,
+                traffic_data,
+                |_, _, _, _| {},
+            );
+            fitness(fitness_value, driving_cars, waiting_cars)
+        })
+        .collect()
 }
 
 pub fn simulate_population(
@@ -227,3 +546,103 @@ pub fn simulate_population(
 
     values
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phases::parse_phases;
+
+    fn traffic(main_from_prev: i32, main_from_next: i32, side: i32) -> TrafficState {
+        TrafficState {
+            main_from_prev,
+            main_from_next,
+            side,
+        }
+    }
+
+    /// Two intersections, the first with 10 cars on the main road and the second with `waiting`
+    /// cars in the approach the only link between them leads to.
+    fn build_test_data(capacity: Option<i32>, waiting: i32) -> (SimulationData, GenerationData) {
+        let generation_data = GenerationData {
+            intersections: 2,
+            timesteps: 1,
+            main_max_count: 20,
+            side_max_count: 10,
+            main_min_count: 7,
+            side_min_count: 3,
+            phases: vec![parse_phases("side,main").unwrap(); 2],
+            phase_bits: 1,
+        };
+        let simulation_data = SimulationData {
+            traffic_data: vec![vec![traffic(10, 0, 0)], vec![traffic(waiting, 0, 0)]],
+            disable_increasing_passthrough: false,
+            disable_max_passthrough: false,
+            max_passthroughs: vec![traffic(16, 16, 16); 2],
+            timestep_length: 10.0,
+            yellow_time: 0.0,
+            all_red_time: 0.0,
+            startup_lost_time: 0.0,
+            link_capacity: capacity,
+            arrivals: String::from("deterministic"),
+            sample_count: 1,
+            dispersion: 2.0,
+            robust_fitness: String::from("mean"),
+            cvar_alpha: 0.2,
+            samples: Vec::new(),
+            links: vec![Link {
+                from: 0,
+                from_approach: 0,
+                to: 1,
+                to_approach: 0,
+                turn: 0,
+                ratio: 1.0,
+                capacity,
+                travel_time: 1,
+            }],
+            turn_shares: vec![vec![1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]; 2],
+        };
+        (simulation_data, generation_data)
+    }
+
+    /// The first intersection shows main green, the second side green.
+    fn build_test_plan() -> Vec<BitVec> {
+        vec![BitVec::from_elem(1, true), BitVec::from_elem(1, false)]
+    }
+
+    #[test]
+    fn inflow_is_limited_to_the_free_places() {
+        assert_eq!(limit_inflow(10, 4, 1.0), 4);
+        assert_eq!(limit_inflow(10, 4, 0.5), 9);
+        assert_eq!(limit_inflow(10, -2, 1.0), 0);
+        assert_eq!(limit_inflow(10, 0, 0.0), 10);
+    }
+
+    #[test]
+    fn links_without_capacity_take_all_passing_cars() {
+        let (simulation_data, generation_data) = build_test_data(None, 4);
+        let trace = trace_simulation(&build_test_plan(), &simulation_data, &generation_data);
+        assert_eq!(trace.passed[0][0].main_from_prev, 10);
+        assert_eq!(trace.blocked, vec![0]);
+        assert_eq!(trace.states[1][1].main_from_prev, 14);
+    }
+
+    #[test]
+    fn full_links_hold_back_cars() {
+        let (simulation_data, generation_data) = build_test_data(Some(6), 4);
+        let trace = trace_simulation(&build_test_plan(), &simulation_data, &generation_data);
+        assert_eq!(trace.passed[0][0].main_from_prev, 2);
+        assert_eq!(trace.blocked, vec![8]);
+        assert_eq!(trace.states[1][0].main_from_prev, 8);
+        assert_eq!(trace.states[1][1].main_from_prev, 6);
+        assert_eq!(trace.gridlock_step, None);
+    }
+
+    #[test]
+    fn gridlock_is_the_first_step_without_passing_cars() {
+        let (simulation_data, generation_data) = build_test_data(Some(4), 4);
+        let trace = trace_simulation(&build_test_plan(), &simulation_data, &generation_data);
+        assert_eq!(trace.passed[0][0].main_from_prev, 0);
+        assert_eq!(trace.blocked, vec![10]);
+        assert_eq!(trace.gridlock_step, Some(0));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::utils::get_mean_value;
+use crate::utils::get_variance;
+
+/// Two-sided 95% critical values of the t-distribution for 1 to 30 degrees of freedom.
+const T_CRITICAL_VALUES: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+#[derive(Debug, Clone)]
+pub struct Statistics {
+    pub mean: f64,
+    pub standard_deviation: Option<f64>,
+    pub min: f64,
+    pub first_quartile: f64,
+    pub median: f64,
+    pub third_quartile: f64,
+    pub max: f64,
+    pub confidence_interval: Option<(f64, f64)>,
+}
+
+/// Quantile with linear interpolation between the closest ranks.
+pub fn get_quantile(values: &Vec<f64>, quantile: f64) -> f64 {
+    let mut sorted = values.clone();
+    sorted.sort_by(|value1, value2| value1.total_cmp(value2));
+    let position = quantile * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+pub fn get_median(values: &Vec<f64>) -> f64 {
+    get_quantile(values, 0.5)
+}
+
+/// Sample standard deviation, undefined for less than two values.
+pub fn get_standard_deviation(values: &Vec<f64>) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    Some(get_variance(values).sqrt())
+}
+
+fn t_critical_value(degrees_of_freedom: usize) -> f64 {
+    if degrees_of_freedom <= 30 {
+        return T_CRITICAL_VALUES[degrees_of_freedom - 1];
+    } else if degrees_of_freedom <= 40 {
+        return 2.021;
+    } else if degrees_of_freedom <= 60 {
+        return 2.000;
+    } else if degrees_of_freedom <= 120 {
+        return 1.980;
+    }
+    1.960
+}
+
+/// 95% confidence interval of the mean based on the t-distribution, undefined for less than two
+/// values.
+pub fn get_confidence_interval(values: &Vec<f64>) -> Option<(f64, f64)> {
+    let standard_deviation = get_standard_deviation(values)?;
+    let mean = get_mean_value(values);
+    let margin =
+        t_critical_value(values.len() - 1) * standard_deviation / (values.len() as f64).sqrt();
+    Some((mean - margin, mean + margin))
+}
+
+pub fn get_statistics(values: &Vec<f64>) -> Statistics {
+    Statistics {
+        mean: get_mean_value(values),
+        standard_deviation: get_standard_deviation(values),
+        min: get_quantile(values, 0.0),
+        first_quartile: get_quantile(values, 0.25),
+        median: get_median(values),
+        third_quartile: get_quantile(values, 0.75),
+        max: get_quantile(values, 1.0),
+        confidence_interval: get_confidence_interval(values),
+    }
+}
+
+/// Values of all runs per iteration, runs stopped early keep their last value.
+pub fn get_convergence_values(run_values: &Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let iterations = run_values
+        .iter()
+        .map(|values| values.len())
+        .max()
+        .unwrap_or(0);
+    (0..iterations)
+        .map(|iteration| {
+            run_values
+                .iter()
+                .filter(|values| !values.is_empty())
+                .map(|values| values[iteration.min(values.len() - 1)])
+                .collect()
+        })
+        .collect()
+}
+
+/// Ranks starting at 1, tied values get the mean of their ranks. Also returns the tie correction
+/// sum of `t^3 - t` over all groups of `t` tied values.
+fn get_ranks(values: &Vec<f64>) -> (Vec<f64>, f64) {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|index1, index2| values[*index1].total_cmp(&values[*index2]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut tie_correction = 0.0;
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start;
+        while end + 1 < order.len() && values[order[end + 1]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        for index in start..=end {
+            ranks[order[index]] = rank;
+        }
+        let tied = (end - start + 1) as f64;
+        tie_correction += tied.powi(3) - tied;
+        start = end + 1;
+    }
+    (ranks, tie_correction)
+}
+
+/// Error function approximation by Abramowitz and Stegun 7.1.26.
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let polynomial = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let value = 1.0 - polynomial * (-x * x).exp();
+    if x < 0.0 {
+        -value
+    } else {
+        value
+    }
+}
+
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / 2.0_f64.sqrt()))
+}
+
+/// Two-sided p-value of a standard normal test statistic with continuity correction.
+fn two_sided_p_value(statistic: f64, mean: f64, standard_deviation: f64) -> f64 {
+    if standard_deviation == 0.0 {
+        return 1.0;
+    }
+    let z = ((statistic - mean).abs() - 0.5).max(0.0) / standard_deviation;
+    (2.0 * (1.0 - normal_cdf(z))).min(1.0)
+}
+
+/// Mann-Whitney U test of two independent samples with the normal approximation, returns U of
+/// the first sample and the two-sided p-value.
+pub fn mann_whitney_u(values1: &Vec<f64>, values2: &Vec<f64>) -> (f64, f64) {
+    let n1 = values1.len() as f64;
+    let n2 = values2.len() as f64;
+    let n = n1 + n2;
+    let mut combined = values1.clone();
+    combined.extend(values2.iter());
+    let (ranks, tie_correction) = get_ranks(&combined);
+
+    let rank_sum: f64 = ranks[..values1.len()].iter().sum();
+    let u = rank_sum - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let standard_deviation =
+        (n1 * n2 / 12.0 * ((n + 1.0) - tie_correction / (n * (n - 1.0)))).sqrt();
+    (u, two_sided_p_value(u, mean, standard_deviation))
+}
+
+/// Wilcoxon signed-rank test of paired samples with the normal approximation, zero differences
+/// are dropped. Returns the sum of positive ranks and the two-sided p-value.
+pub fn wilcoxon_signed_rank(values1: &Vec<f64>, values2: &Vec<f64>) -> (f64, f64) {
+    let differences: Vec<f64> = values1
+        .iter()
+        .zip(values2.iter())
+        .map(|(value1, value2)| value1 - value2)
+        .filter(|difference| *difference != 0.0)
+        .collect();
+    if differences.is_empty() {
+        return (0.0, 1.0);
+    }
+    let n = differences.len() as f64;
+    let (ranks, tie_correction) = get_ranks(
+        &differences
+            .iter()
+            .map(|difference| difference.abs())
+            .collect(),
+    );
+
+    let positive_rank_sum: f64 = differences
+        .iter()
+        .zip(ranks.iter())
+        .filter(|(difference, _)| **difference > 0.0)
+        .map(|(_, rank)| rank)
+        .sum();
+    let mean = n * (n + 1.0) / 4.0;
+    let standard_deviation =
+        (n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - tie_correction / 48.0).sqrt();
+    (
+        positive_rank_sum,
+        two_sided_p_value(positive_rank_sum, mean, standard_deviation),
+    )
+}
+
+/// Probability that a value of the first sample is larger than one of the second, ties count half.
+pub fn vargha_delaney_a12(values1: &Vec<f64>, values2: &Vec<f64>) -> f64 {
+    let mut wins = 0.0;
+    for value1 in values1.iter() {
+        for value2 in values2.iter() {
+            if value1 > value2 {
+                wins += 1.0;
+            } else if value1 == value2 {
+                wins += 0.5;
+            }
+        }
+    }
+    wins / (values1.len() * values2.len()) as f64
+}
+
+/// Magnitude of an A12 effect size with the thresholds of Vargha and Delaney.
+pub fn effect_size_magnitude(a12: f64) -> &'static str {
+    let distance = (a12 - 0.5).abs();
+    if distance < 0.06 {
+        "negligible"
+    } else if distance < 0.14 {
+        "small"
+    } else if distance < 0.21 {
+        "medium"
+    } else {
+        "large"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!(
+            (value - expected).abs() < 1e-9,
+            "{} is not {}",
+            value,
+            expected
+        );
+    }
+
+    #[test]
+    fn quantiles_interpolate_between_ranks() {
+        let values = vec![4.0, 1.0, 3.0, 2.0];
+        assert_close(get_quantile(&values, 0.0), 1.0);
+        assert_close(get_quantile(&values, 0.25), 1.75);
+        assert_close(get_median(&values), 2.5);
+        assert_close(get_quantile(&values, 1.0), 4.0);
+        assert_close(get_quantile(&vec![7.0], 0.75), 7.0);
+    }
+
+    #[test]
+    fn quantiles_do_not_panic_on_nan() {
+        let values = vec![1.0, f64::NAN, 2.0];
+        assert_close(get_quantile(&values, 0.0), 1.0);
+    }
+
+    #[test]
+    fn t_critical_values_follow_the_table() {
+        assert_close(t_critical_value(1), 12.706);
+        assert_close(t_critical_value(30), 2.042);
+        assert_close(t_critical_value(35), 2.021);
+        assert_close(t_critical_value(1000), 1.960);
+    }
+
+    #[test]
+    fn confidence_interval_of_the_mean() {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let (low, high) = get_confidence_interval(&values).unwrap();
+        let margin = 2.776 * 2.5_f64.sqrt() / 5.0_f64.sqrt();
+        assert_close(low, 3.0 - margin);
+        assert_close(high, 3.0 + margin);
+    }
+
+    #[test]
+    fn single_value_has_no_spread() {
+        let statistics = get_statistics(&vec![0.5]);
+        assert_close(statistics.mean, 0.5);
+        assert!(statistics.standard_deviation.is_none());
+        assert!(statistics.confidence_interval.is_none());
+    }
+
+    #[test]
+    fn ties_share_their_mean_rank() {
+        let (ranks, tie_correction) = get_ranks(&vec![3.0, 1.0, 3.0, 2.0]);
+        assert_eq!(ranks, vec![3.5, 1.0, 3.5, 2.0]);
+        assert_close(tie_correction, 6.0);
+    }
+}
@@ -0,0 +1,286 @@
+use crate::cli::build_configuration_data;
+use crate::cli::build_optimization_data;
+use crate::cli::build_scenario;
+use crate::cli::GenerationArgs;
+use crate::cli::OptimizerArgs;
+use crate::cli::SimulationArgs;
+use crate::data::ConfigurationData;
+use crate::data::GenerationData;
+use crate::data::OptimizationData;
+use crate::data::OptimizationResult;
+use crate::data::PlotData;
+use crate::data::SimulationData;
+use crate::optimization::optimize;
+use crate::parameters::check_optimization_data;
+use crate::parameters::parse_assignments;
+use crate::parameters::set_optimization_parameter;
+use crate::parameters::set_scenario_parameter;
+use crate::parameters::SCENARIO_PARAMETERS;
+use crate::utils::seed_generator;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+pub struct SweepParameter {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+pub struct SweepData {
+    pub grid: Vec<SweepParameter>,
+    pub scenarios: Vec<String>,
+    pub repetitions: usize,
+    pub threads: usize,
+    pub seed: u64,
+    pub results_file: Option<String>,
+}
+
+/// One scenario and combination of grid values with everything needed to run it.
+struct SweepSetting {
+    scenario: usize,
+    combination: Vec<usize>,
+    configuration_data: ConfigurationData,
+    generation_data: GenerationData,
+    simulation_data: SimulationData,
+    optimization_data: OptimizationData,
+}
+
+/// Parses grid parameters given as `name=value1,value2,...`.
+pub fn parse_grid(specifications: &Vec<String>) -> Result<Vec<SweepParameter>, String> {
+    let mut grid: Vec<SweepParameter> = Vec::with_capacity(specifications.len());
+    for specification in specifications.iter() {
+        match specification.split_once('=') {
+            Some((name, values)) => grid.push(SweepParameter {
+                name: name.trim().to_string(),
+                values: values
+                    .split(',')
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+                    .collect(),
+            }),
+            None => {
+                return Err(format!(
+                    "Expected name=value1,value2,... but got {}",
+                    specification
+                ))
+            }
+        }
+    }
+    Ok(grid)
+}
+
+/// All combinations of value indices of the grid parameters.
+fn build_combinations(grid: &Vec<SweepParameter>) -> Vec<Vec<usize>> {
+    let mut combinations: Vec<Vec<usize>> = vec![Vec::new()];
+    for parameter in grid.iter() {
+        let mut extended: Vec<Vec<usize>> = Vec::new();
+        for combination in combinations.iter() {
+            for index in 0..parameter.values.len() {
+                let mut next = combination.clone();
+                next.push(index);
+                extended.push(next);
+            }
+        }
+        combinations = extended;
+    }
+    combinations
+}
+
+fn build_setting(
+    scenario: usize,
+    combination: &Vec<usize>,
+    sweep_data: &SweepData,
+    generation_args: &GenerationArgs,
+    simulation_args: &SimulationArgs,
+    optimizer_args: &OptimizerArgs,
+) -> Result<SweepSetting, String> {
+    let mut generation_args = generation_args.clone();
+    let mut simulation_args = simulation_args.clone();
+    let mut optimizer_assignments: Vec<(&str, &str)> = Vec::new();
+
+    let scenario_assignments = parse_assignments(&sweep_data.scenarios[scenario])?;
+    for (name, value) in scenario_assignments.iter() {
+        set_scenario_parameter(&mut generation_args, &mut simulation_args, name, value)?;
+    }
+    for (parameter, index) in sweep_data.grid.iter().zip(combination.iter()) {
+        let value = &parameter.values[*index];
+        if SCENARIO_PARAMETERS.contains(&parameter.name.as_str()) {
+            set_scenario_parameter(
+                &mut generation_args,
+                &mut simulation_args,
+                &parameter.name,
+                value,
+            )?;
+        } else {
+            optimizer_assignments.push((&parameter.name, value));
+        }
+    }
+
+    let mut configuration_data =
+        build_configuration_data(Some(sweep_data.seed), &simulation_args.data);
+    configuration_data.silent = true;
+    configuration_data.quiet = true;
+    let (generation_data, simulation_data) =
+        build_scenario(&configuration_data, &generation_args, &simulation_args)?;
+    let mut optimization_data = build_optimization_data(optimizer_args, &generation_data)?;
+    for (name, value) in optimizer_assignments.iter() {
+        set_optimization_parameter(&mut optimization_data, name, value)?;
+    }
+    check_optimization_data(&optimization_data)?;
+
+    Ok(SweepSetting {
+        scenario,
+        combination: combination.clone(),
+        configuration_data,
+        generation_data,
+        simulation_data,
+        optimization_data,
+    })
+}
+
+fn run_setting(setting: &SweepSetting, seed: u64) -> OptimizationResult {
+    seed_generator(seed);
+    optimize(
+        &setting.configuration_data,
+        &setting.optimization_data,
+        &setting.simulation_data,
+        &setting.generation_data,
+        &mut PlotData::default(),
+    )
+}
+
+fn format_results(
+    sweep_data: &SweepData,
+    settings: &Vec<SweepSetting>,
+    results: &Vec<Option<OptimizationResult>>,
+) -> String {
+    let mut content = String::from("scenario");
+    for parameter in sweep_data.grid.iter() {
+        content.push_str(&format!(",{}", parameter.name));
+    }
+    content.push_str(",repetition,seed,best_value,iterations,evaluations,duration\n");
+
+    for (job, result) in results.iter().enumerate() {
+        let result = match result {
+            Some(result) => result,
+            None => continue,
+        };
+        let setting = &settings[job / sweep_data.repetitions];
+        let repetition = job % sweep_data.repetitions;
+        content.push_str(&format!("\"{}\"", sweep_data.scenarios[setting.scenario]));
+        for (parameter, index) in sweep_data.grid.iter().zip(setting.combination.iter()) {
+            content.push_str(&format!(",{}", parameter.values[*index]));
+        }
+        content.push_str(&format!(
+            ",{},{},{},{},{},{}\n",
+            repetition,
+            sweep_data.seed + repetition as u64,
+            result.best_value,
+            result.iterations,
+            result.evaluations,
+            result.duration
+        ));
+    }
+    content
+}
+
+/// Runs every combination of grid values on every scenario with the configured repetitions.
+/// All settings are built before the first run so invalid values fail early, runs are
+/// distributed over the threads and reseed their generator, so the results do not depend on
+/// the number of threads.
+pub fn sweep(
+    sweep_data: &SweepData,
+    generation_args: &GenerationArgs,
+    simulation_args: &SimulationArgs,
+    optimizer_args: &OptimizerArgs,
+) -> Result<(), String> {
+    if sweep_data.repetitions < 1 || sweep_data.threads < 1 {
+        return Err(String::from(
+            "Sweeping needs at least one repetition and one thread",
+        ));
+    }
+    let mut settings: Vec<SweepSetting> = Vec::new();
+    for scenario in 0..sweep_data.scenarios.len() {
+        for combination in build_combinations(&sweep_data.grid).iter() {
+            settings.push(build_setting(
+                scenario,
+                combination,
+                sweep_data,
+                generation_args,
+                simulation_args,
+                optimizer_args,
+            )?);
+        }
+    }
+
+    let jobs = settings.len() * sweep_data.repetitions;
+    let next_job = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<OptimizationResult>>> =
+        Mutex::new((0..jobs).map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..sweep_data.threads.min(jobs) {
+            scope.spawn(|| loop {
+                let job = next_job.fetch_add(1, Ordering::SeqCst);
+                if job >= jobs {
+                    break;
+                }
+                let repetition = job % sweep_data.repetitions;
+                let result = run_setting(
+                    &settings[job / sweep_data.repetitions],
+                    sweep_data.seed + repetition as u64,
+                );
+                results.lock().unwrap()[job] = Some(result);
+            });
+        }
+    });
+
+    let content = format_results(sweep_data, &settings, &results.into_inner().unwrap());
+    match &sweep_data.results_file {
+        Some(path) => {
+            fs::write(path, content)
+                .map_err(|error| format!("Could not write results to {}: {}", path, error))?;
+            println!("Wrote {} results to {}", jobs, path);
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_grid_splits_names_and_values() {
+        let grid = parse_grid(&vec![
+            String::from("population_size=20, 50,"),
+            String::from(" mutation = bitflip"),
+        ])
+        .unwrap();
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid[0].name, "population_size");
+        assert_eq!(grid[0].values, vec!["20", "50"]);
+        assert_eq!(grid[1].name, "mutation");
+        assert_eq!(grid[1].values, vec!["bitflip"]);
+    }
+
+    #[test]
+    fn parse_grid_rejects_missing_values() {
+        assert!(parse_grid(&vec![String::from("population_size")]).is_err());
+    }
+
+    #[test]
+    fn combinations_cover_the_whole_grid() {
+        let grid = parse_grid(&vec![
+            String::from("population_size=20,50"),
+            String::from("mutation=none,bitflip,prob_bitflip"),
+        ])
+        .unwrap();
+        let combinations = build_combinations(&grid);
+        assert_eq!(combinations.len(), 6);
+        assert_eq!(combinations[0], vec![0, 0]);
+        assert_eq!(combinations[5], vec![1, 2]);
+        assert_eq!(build_combinations(&Vec::new()), vec![Vec::<usize>::new()]);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::data::OptimizationData;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminationState {
+    #[serde(
+        rename = "elapsed",
+        serialize_with = "serialize_elapsed",
+        deserialize_with = "deserialize_elapsed"
+    )]
+    pub start: Instant,
+    pub iteration: usize,
+    pub evaluations: usize,
+    pub last_improvement: usize,
+    pub reason: String,
+}
+
+fn serialize_elapsed<S: Serializer>(start: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(start.elapsed().as_secs_f64())
+}
+
+/// Moves the start back by the elapsed seconds, so time limits keep counting the time spent
+/// before a checkpoint was written.
+fn deserialize_elapsed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+    let elapsed = f64::deserialize(deserializer)?;
+    let now = Instant::now();
+    Ok(now
+        .checked_sub(Duration::from_secs_f64(elapsed))
+        .unwrap_or(now))
+}
+
+pub fn build_termination_state() -> TerminationState {
+    TerminationState {
+        start: Instant::now(),
+        iteration: 0,
+        evaluations: 0,
+        last_improvement: 0,
+        reason: String::new(),
+    }
+}
+
+pub fn record_improvement(termination_state: &mut TerminationState) {
+    termination_state.last_improvement = termination_state.iteration;
+}
+
+fn terminate(termination_state: &mut TerminationState, reason: &str) -> bool {
+    termination_state.reason = String::from(reason);
+    true
+}
+
+/// Checks all stopping rules before the next iteration, which will need `next_evaluations`
+/// simulations. The first rule that applies is stored as the reason.
+pub fn should_terminate(
+    termination_state: &mut TerminationState,
+    best_value: f64,
+    next_evaluations: usize,
+    optimization_data: &OptimizationData,
+) -> bool {
+    if termination_state.iteration >= optimization_data.iterations {
+        return terminate(termination_state, "iterations");
+    }
+    if let Some(stagnation_iterations) = optimization_data.stagnation_iterations {
+        if termination_state.iteration - termination_state.last_improvement >= stagnation_iterations
+        {
+            return terminate(termination_state, "stagnation");
+        }
+    }
+    if let Some(target_value) = optimization_data.target_value {
+        if best_value >= target_value {
+            return terminate(termination_state, "target value");
+        }
+    }
+    if let Some(time_limit) = optimization_data.time_limit {
+        if termination_state.start.elapsed().as_secs_f64() >= time_limit {
+            return terminate(termination_state, "time limit");
+        }
+    }
+    if let Some(max_evaluations) = optimization_data.max_evaluations {
+        if termination_state.evaluations + next_evaluations > max_evaluations {
+            return terminate(termination_state, "evaluations");
+        }
+    }
+    false
+}
+
+pub fn print_termination(termination_state: &TerminationState) {
+    println!(
+        "Stopped after {} iterations and {} evaluations in {:.4}s ({})",
+        termination_state.iteration,
+        termination_state.evaluations,
+        termination_state.start.elapsed().as_secs_f64(),
+        termination_state.reason
+    );
+}
@@ -0,0 +1,183 @@
+use crate::data::ConfigurationData;
+use crate::data::GenerationData;
+use crate::data::OptimizationData;
+use crate::data::PlotData;
+use crate::data::SimulationData;
+use crate::data::TuningData;
+use crate::optimization::optimize;
+use crate::parameters::check_optimization_data;
+use crate::utils::distinct_random;
+use crate::utils::get_mean_value;
+use crate::utils::get_variance;
+
+struct TuningCandidate {
+    optimization_data: OptimizationData,
+    values: Vec<f64>,
+}
+
+fn parameter_space_size(tuning_data: &TuningData) -> usize {
+    tuning_data.population_sizes.len()
+        * tuning_data.parents_sizes.len()
+        * tuning_data.tournament_sizes.len()
+        * tuning_data.probabilities_bitflip.len()
+        * tuning_data.probabilities_recombination.len()
+}
+
+fn build_configuration(
+    mut index: usize,
+    optimization_data: &OptimizationData,
+    tuning_data: &TuningData,
+) -> OptimizationData {
+    let mut configuration = optimization_data.clone();
+
+    configuration.population_size =
+        tuning_data.population_sizes[index % tuning_data.population_sizes.len()];
+    index /= tuning_data.population_sizes.len();
+    configuration.parents_size = tuning_data.parents_sizes[index % tuning_data.parents_sizes.len()];
+    index /= tuning_data.parents_sizes.len();
+    configuration.tournament_size =
+        tuning_data.tournament_sizes[index % tuning_data.tournament_sizes.len()];
+    index /= tuning_data.tournament_sizes.len();
+    configuration.probability_bitflip =
+        tuning_data.probabilities_bitflip[index % tuning_data.probabilities_bitflip.len()];
+    index /= tuning_data.probabilities_bitflip.len();
+    configuration.probability_recombination = tuning_data.probabilities_recombination
+        [index % tuning_data.probabilities_recombination.len()];
+
+    configuration
+}
+
+fn sample_configurations(
+    optimization_data: &OptimizationData,
+    tuning_data: &TuningData,
+) -> Vec<TuningCandidate> {
+    let space_size = parameter_space_size(tuning_data);
+    let indices: Vec<usize> = if space_size <= tuning_data.configurations {
+        (0..space_size).collect()
+    } else {
+        distinct_random(0, space_size, tuning_data.configurations)
+    };
+
+    let mut candidates: Vec<TuningCandidate> = Vec::with_capacity(indices.len());
+    for index in indices {
+        let configuration = build_configuration(index, optimization_data, tuning_data);
+        if check_optimization_data(&configuration).is_err() {
+            println!(
+                "Skipping invalid configuration {}",
+                describe(&configuration)
+            );
+            continue;
+        }
+        candidates.push(TuningCandidate {
+            optimization_data: configuration,
+            values: Vec::new(),
+        });
+    }
+    candidates
+}
+
+pub fn describe(optimization_data: &OptimizationData) -> String {
+    format!(
+        "population_size={} parents_size={} tournament_size={} probability_bitflip={} probability_recombination={}",
+        optimization_data.population_size,
+        optimization_data.parents_size,
+        optimization_data.tournament_size,
+        optimization_data.probability_bitflip,
+        optimization_data.probability_recombination
+    )
+}
+
+fn race(
+    candidate: &mut TuningCandidate,
+    repetitions: usize,
+    configuration_data: &ConfigurationData,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+) {
+    while candidate.values.len() < repetitions {
+        let result = optimize(
+            configuration_data,
+            &candidate.optimization_data,
+            simulation_data,
+            generation_data,
+            &mut PlotData::default(),
+        );
+        candidate.values.push(result.best_value);
+    }
+}
+
+/// Random search over the parameter space followed by successive halving: every round all
+/// remaining configurations are run until they reach the round's number of repetitions, then
+/// the better half by mean best value advances and the repetitions are doubled.
+pub fn tune(
+    configuration_data: &ConfigurationData,
+    optimization_data: &OptimizationData,
+    simulation_data: &SimulationData,
+    generation_data: &GenerationData,
+    tuning_data: &TuningData,
+) {
+    let mut run_configuration_data = configuration_data.clone();
+    run_configuration_data.silent = true;
+    run_configuration_data.quiet = true;
+    run_configuration_data.plot = false;
+    run_configuration_data.print_final_simulation = false;
+
+    let mut candidates = sample_configurations(optimization_data, tuning_data);
+    if candidates.is_empty() {
+        println!("No valid configuration in the parameter space");
+        return;
+    }
+
+    let mut repetitions = tuning_data.repetitions;
+    let mut round = 1;
+    loop {
+        for candidate in candidates.iter_mut() {
+            race(
+                candidate,
+                repetitions,
+                &run_configuration_data,
+                simulation_data,
+                generation_data,
+            );
+        }
+        candidates.sort_by(|candidate1, candidate2| {
+            get_mean_value(&candidate2.values).total_cmp(&get_mean_value(&candidate1.values))
+        });
+
+        println!(
+            "Round {}: {} configurations with {} repetitions",
+            round,
+            candidates.len(),
+            repetitions
+        );
+        for candidate in candidates.iter() {
+            println!(
+                "{:.4}\t{:.6}\t{}",
+                get_mean_value(&candidate.values),
+                get_variance(&candidate.values),
+                describe(&candidate.optimization_data)
+            );
+        }
+
+        if candidates.len() == 1 {
+            break;
+        }
+        candidates.truncate(candidates.len().div_ceil(2));
+        repetitions *= 2;
+        round += 1;
+    }
+
+    let best = &candidates[0];
+    println!("Best configuration:");
+    println!("{}", describe(&best.optimization_data));
+    println!(
+        "Mean of best individual over {} runs: {:.4}",
+        best.values.len(),
+        get_mean_value(&best.values)
+    );
+    println!(
+        "Variance of best individual over {} runs: {:.6}",
+        best.values.len(),
+        get_variance(&best.values)
+    );
+}
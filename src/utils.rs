@@ -1,8 +1,72 @@
 use bit_vec::BitVec;
-use rand::Rng;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 
 use crate::data::OptimizationData;
 
+thread_local! {
+    static GENERATOR: RefCell<ChaCha8Rng> = RefCell::new(ChaCha8Rng::from_entropy());
+}
+
+/// Handle to the seedable random number generator of the current thread. Used instead of
+/// `rand::thread_rng()` so runs can be reproduced with a seed and resumed from checkpoints.
+pub struct Generator;
+
+impl RngCore for Generator {
+    fn next_u32(&mut self) -> u32 {
+        GENERATOR.with(|generator| generator.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        GENERATOR.with(|generator| generator.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        GENERATOR.with(|generator| generator.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        GENERATOR.with(|generator| generator.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratorState {
+    pub seed: [u8; 32],
+    pub stream: u64,
+    pub word_pos: u128,
+}
+
+pub fn generator() -> Generator {
+    Generator
+}
+
+pub fn seed_generator(seed: u64) {
+    GENERATOR.with(|generator| *generator.borrow_mut() = ChaCha8Rng::seed_from_u64(seed));
+}
+
+pub fn get_generator_state() -> GeneratorState {
+    GENERATOR.with(|generator| {
+        let generator = generator.borrow();
+        GeneratorState {
+            seed: generator.get_seed(),
+            stream: generator.get_stream(),
+            word_pos: generator.get_word_pos(),
+        }
+    })
+}
+
+pub fn restore_generator_state(generator_state: &GeneratorState) {
+    GENERATOR.with(|generator| {
+        let mut restored = ChaCha8Rng::from_seed(generator_state.seed);
+        restored.set_stream(generator_state.stream);
+        restored.set_word_pos(generator_state.word_pos);
+        *generator.borrow_mut() = restored;
+    })
+}
+
 pub fn get_highest_and_lowest(values: &Vec<f64>) -> (usize, usize) {
     let mut highest_index: usize = 0;
     let mut lowest_index: usize = 0;
@@ -36,8 +100,20 @@ pub fn get_mean_value(values: &Vec<f64>) -> f64 {
     values.iter().sum::<f64>() / values.len() as f64
 }
 
+pub fn get_variance(values: &Vec<f64>) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = get_mean_value(values);
+    values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / (values.len() - 1) as f64
+}
+
 pub fn distinct_random(min: usize, max: usize, count: usize) -> Vec<usize> {
-    let mut rng = rand::thread_rng();
+    let mut rng = generator();
     let mut random_values: Vec<usize> = Vec::with_capacity(count);
     while random_values.len() != count {
         let random_index = rng.gen_range(min..max);
@@ -52,7 +128,7 @@ pub fn tournament(
     population_values: &Vec<f64>,
     optimization_data: &OptimizationData,
 ) -> Vec<usize> {
-    let mut rng = rand::thread_rng();
+    let mut rng = generator();
     let mut winners: Vec<usize> = Vec::with_capacity(optimization_data.parents_size);
 
     for _ in 0..optimization_data.parents_size {